@@ -1,11 +1,12 @@
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use crate::game::entity::card::CardRef;
+use crate::game::entity::card::CardView;
 
-#[derive(Serialize, Clone, Deserialize, Debug)]
+#[derive(Serialize, Clone, Deserialize, Debug, JsonSchema)]
 pub struct BoardView {
-    pub creatures: [Option<CardRef>; 6],
-    pub artifacts: [Option<CardRef>; 3],
-    pub enchantments: [Option<CardRef>; 3],
+    pub creatures: [Option<CardView>; 6],
+    pub artifacts: [Option<CardView>; 3],
+    pub enchantments: [Option<CardView>; 3],
 }
 
 impl Default for BoardView {
@@ -18,9 +19,22 @@ impl Default for BoardView {
     }
 }
 
-#[derive(Serialize, Clone, Deserialize, Debug, Default)]
+impl BoardView {
+    /// Returns the creature with the given instance id, if it is on the board.
+    pub fn creature(&self, card_id: &str) -> Option<&CardView> {
+        self.creatures.iter().flatten().find(|c| c.id == card_id)
+    }
+
+    /// Returns a mutable reference to the creature with the given instance id, if it is on the
+    /// board.
+    pub fn creature_mut(&mut self, card_id: &str) -> Option<&mut CardView> {
+        self.creatures.iter_mut().flatten().find(|c| c.id == card_id)
+    }
+}
+
+#[derive(Serialize, Clone, Deserialize, Debug, Default, JsonSchema)]
 pub struct GraveyardView {
-    pub creatures: Vec<CardRef>,
-    pub artifacts: Vec<CardRef>,
-    pub enchantments: Vec<CardRef>,
+    pub creatures: Vec<CardView>,
+    pub artifacts: Vec<CardView>,
+    pub enchantments: Vec<CardView>,
 }
@@ -2,9 +2,10 @@ use crate::models::http_response::SelectedCardsResponse;
 use crate::utils::errors::CardRequestError;
 use crate::SETTINGS;
 use reqwest::StatusCode;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone, JsonSchema)]
 pub struct CardRef {
     pub id: String,
     pub amount: u32,
@@ -99,12 +100,18 @@ impl Card {
     }
 }
 
-#[derive(Serialize, Clone, Debug, Deserialize)]
+/// A physical card of the match, in a deck, hand, board or graveyard.
+///
+/// `id` identifies this copy for the whole match, while `card_id` is the catalog id it
+/// shares with every other copy of the same card.
+#[derive(Serialize, Clone, Debug, Deserialize, JsonSchema)]
 pub struct CardView {
     pub id: String,
+    pub card_id: String,
     pub name: String,
     pub attack: i32,
     pub health: i32,
+    pub max_health: i32,
     pub play_cost: i32,
     
     pub owner_id: String,
@@ -119,16 +126,18 @@ pub struct CardView {
 }
 
 impl CardView {
-    pub fn create_view(card: &Card, owner_id: String) -> Self {
+    pub fn create_view(card: &Card, instance_id: String, owner_id: String) -> Self {
         CardView {
             position: None,
             owner_id: owner_id,
             is_exhausted: false,
-            id: card.id.clone(),
+            id: instance_id,
+            card_id: card.id.clone(),
             effects: Vec::new(),
             name: card.name.clone(),
             attack: card.attack.clone(),
             health: card.health.clone(),
+            max_health: card.health,
             play_cost: card.play_cost.clone(),
             in_deck: false,
             in_hand: false,
@@ -12,14 +12,16 @@ pub struct Deck {
 }
 
 impl Deck {
-    pub fn create_view(&self, cards: &HashMap<String, Card>, owner_id: &str) -> DeckView {
-        let mut card_views: HashMap<String, CardView> = HashMap::new();
-        for card in &self.cards {
-            let full_card = cards.get(&card.id).unwrap();
-            let view = CardView::create_view(full_card, owner_id.to_string());
-            card_views.insert(card.id.to_string(), view);
-        }
-        
+    /// Creates the view of the deck, holding each of its physical cards by instance id.
+    ///
+    /// # Arguments
+    /// * `cards` - Every copy of the deck's cards, as created by `draw_pile`.
+    pub fn create_view(&self, cards: &[CardView]) -> DeckView {
+        let card_views: HashMap<String, CardView> = cards
+            .iter()
+            .map(|card| (card.id.clone(), card.clone()))
+            .collect();
+
         DeckView {
             card_views,
             id: self.id.clone(),
@@ -27,6 +29,35 @@ impl Deck {
             player_id: self.player_id.to_string(),
         }
     }
+
+    /// Creates a view of every copy of the deck's cards, in deck list order, ready to be
+    /// shuffled into a draw pile.
+    ///
+    /// Cards missing from `cards` are left out.
+    ///
+    /// # Arguments
+    /// * `cards` - The full cards of the match.
+    /// * `create_card` - Creates a new physical card, with its own instance id.
+    pub fn draw_pile(
+        &self,
+        cards: &HashMap<String, Card>,
+        mut create_card: impl FnMut(&Card) -> CardView,
+    ) -> Vec<CardView> {
+        let mut pile = Vec::new();
+        for card in &self.cards {
+            let Some(full_card) = cards.get(&card.id) else {
+                continue;
+            };
+
+            for _ in 0..card.amount {
+                let mut view = create_card(full_card);
+                view.in_deck = true;
+                pile.push(view);
+            }
+        }
+
+        pile
+    }
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -1,15 +1,20 @@
 use crate::game::entity::board::{BoardView, GraveyardView};
-use crate::game::entity::card::{CardRef, CardView};
+use crate::game::entity::card::CardView;
 use crate::game::entity::deck::{Deck, DeckView};
 use crate::models::client_requests::{ConnectionRequest, ReconnectionRequest};
+use crate::models::game_event::GameEvent;
 use crate::models::http_response::{AuthenticatedPlayer, PreloadedPlayer};
 use crate::{
     logger,
     models::http_response::PartialPlayerProfile,
-    utils::{errors::PlayerConnectionError, logger::Logger},
+    utils::{
+        errors::{GameLogicError, PlayerConnectionError},
+        logger::Logger,
+    },
     SETTINGS,
 };
 use reqwest::{header::AUTHORIZATION, StatusCode};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use tokio::sync::RwLock;
@@ -43,13 +48,10 @@ impl Player {
         }
     }
 
-    pub async fn new_connection(payload: &[u8]) -> Result<AuthenticatedPlayer, PlayerConnectionError> {
-        match serde_cbor::from_slice::<ConnectionRequest>(payload) {
-            Err(error) => Err(PlayerConnectionError::InvalidPlayerPayload(error.to_string())),
-            Ok(request) => {
-                Ok(Player::verify_authentication(&request.auth_token).await?)
-            }
-        }
+    pub async fn new_connection(
+        request: &ConnectionRequest,
+    ) -> Result<AuthenticatedPlayer, PlayerConnectionError> {
+        Player::verify_authentication(&request.auth_token).await
     }
     
     pub async fn preload_player_profile(
@@ -102,27 +104,20 @@ impl Player {
     /// Handles player reconnection by verifying the authentication token and matching the player ID.
     ///
     /// # Arguments
-    /// * `payload` - A byte slice containing the serialized reconnection request.
+    /// * `request` - The deserialized reconnection request.
     ///
     /// # Returns
     /// * `Ok(AuthenticatedPlayer)` - The authenticated player instance.
-    /// * `Err(PlayerConnectionError)` - An error if the payload is invalid or authentication fails.
+    /// * `Err(PlayerConnectionError)` - An error if authentication fails or the player ID does not match.
     pub async fn reconnection(
-        payload: &[u8],
+        request: &ReconnectionRequest,
     ) -> Result<AuthenticatedPlayer, PlayerConnectionError> {
-        match serde_cbor::from_slice::<ReconnectionRequest>(payload) {
-            Err(error) => Err(PlayerConnectionError::InvalidPlayerPayload(
-                error.to_string(),
-            )),
-            Ok(request) => {
-                let player_profile = Player::verify_authentication(&request.auth_token).await?;
-                if player_profile.player_id != request.player_id {
-                    return Err(PlayerConnectionError::PlayerDiscrepancy);
-                }
-
-                Ok(player_profile)
-            }
+        let player_profile = Player::verify_authentication(&request.auth_token).await?;
+        if player_profile.player_id != request.player_id {
+            return Err(PlayerConnectionError::PlayerDiscrepancy);
         }
+
+        Ok(player_profile)
     }
 
     /// Verifies the player's authentication token by contacting the authentication server.
@@ -211,10 +206,19 @@ impl Player {
     }
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+/// Health of a hero at the start of the match, which healing cannot exceed.
+pub const MAX_HEALTH: i32 = 30;
+
+/// Highest maximum mana a player can reach, one more being gained at the start of each turn.
+pub const MAX_MANA: i32 = 10;
+
+#[derive(Serialize, Deserialize, Debug, Clone, JsonSchema)]
 pub struct PlayerView {
     pub id: String,
     pub mana: i32,
+    pub max_mana: i32,
+    pub locked_mana: i32, // Mana unavailable during the current turn.
+    pub overload: i32,    // Mana that will be locked during the player's next turn.
     pub health: i32,
 
     pub hand_size: usize,
@@ -224,13 +228,20 @@ pub struct PlayerView {
     pub board: BoardView,
     pub graveyard_size: usize,
     pub graveyard: GraveyardView,
+
+    // Connection statistics, concealed from the opponent along with the hand
+    pub ping_ms: Option<i64>,
+    pub clock_offset_ms: Option<i64>,
 }
 
 impl PlayerView {
     pub fn from_player(player_id: &str, deck_size: usize) -> Self {
         PlayerView {
-            mana: 1,
-            health: 30,
+            mana: 0,
+            max_mana: 0,
+            locked_mana: 0,
+            overload: 0,
+            health: MAX_HEALTH,
             id: player_id.to_string(),
 
             deck_size,
@@ -239,17 +250,253 @@ impl PlayerView {
             board: BoardView::default(),
             graveyard: GraveyardView::default(),
             current_hand: [None, None, None, None, None, None, None, None, None, None],
+            ping_ms: None,
+            clock_offset_ms: None,
+        }
+    }
+
+    /// Hides what only the player themselves may see, before the view is sent to their
+    /// opponent: the cards in their hand, whose count stays visible, and their connection
+    /// statistics.
+    pub fn conceal(&mut self) {
+        self.current_hand = Default::default();
+        self.ping_ms = None;
+        self.clock_offset_ms = None;
+    }
+
+    /// Returns the health and maximum health of the hero or board creature with the given id.
+    ///
+    /// # Arguments
+    /// * `target_id` - The player's id for their hero, or the id of a creature on their board.
+    pub fn health_mut(&mut self, target_id: &str) -> Option<(&mut i32, i32)> {
+        if self.id == target_id {
+            return Some((&mut self.health, MAX_HEALTH));
+        }
+
+        self.board.creature_mut(target_id).map(|card| {
+            let max_health = card.max_health;
+            (&mut card.health, max_health)
+        })
+    }
+
+    /// Grows the player's maximum mana by one, up to `MAX_MANA`, then refills their mana
+    /// minus the overload locked during their previous turn.
+    pub fn refill_mana(&mut self) {
+        self.max_mana = (self.max_mana + 1).min(MAX_MANA);
+        self.locked_mana = self.overload.min(self.max_mana);
+        self.overload = 0;
+        self.mana = self.max_mana - self.locked_mana;
+    }
+
+    /// Spends mana, leaving it untouched if the player cannot afford it.
+    ///
+    /// # Returns
+    /// `Err(GameLogicError::NotEnoughMana)` if less than `amount` mana is available.
+    pub fn spend_mana(&mut self, amount: i32) -> Result<(), GameLogicError> {
+        if amount > self.mana {
+            return Err(GameLogicError::NotEnoughMana(amount, self.mana));
+        }
+
+        self.mana -= amount.max(0);
+        Ok(())
+    }
+
+    /// Puts a copy of a card in the first free slot of the player's hand.
+    ///
+    /// # Returns
+    /// A copy of the card as placed in the hand, or `None` if the hand is full.
+    pub fn add_to_hand(&mut self, card: &CardView) -> Option<CardView> {
+        let slot = self.current_hand.iter_mut().find(|slot| slot.is_none())?;
+        let mut card = card.clone();
+        card.in_deck = false;
+        card.in_hand = true;
+        self.hand_size += 1;
+        Some(slot.insert(card).clone())
+    }
+
+    /// Removes the card with the given instance id from the player's hand.
+    pub fn take_from_hand(&mut self, instance_id: &str) -> Option<CardView> {
+        let slot = self
+            .current_hand
+            .iter_mut()
+            .find(|slot| slot.as_ref().is_some_and(|card| card.id == instance_id))?;
+
+        self.hand_size -= 1;
+        slot.take().map(|mut card| {
+            card.in_hand = false;
+            card
+        })
+    }
+
+    /// Parses a creature slot of the player's board (`0` to `5`), ensuring it is free.
+    ///
+    /// # Returns
+    /// The slot index, or `InvalidSummonPosition` if it does not exist or is taken.
+    pub fn free_creature_slot(&self, position: &str) -> Result<usize, GameLogicError> {
+        match position.parse::<usize>() {
+            Ok(slot) if matches!(self.board.creatures.get(slot), Some(None)) => Ok(slot),
+            _ => Err(GameLogicError::InvalidSummonPosition(position.to_string())),
+        }
+    }
+
+    /// Places a creature in a free slot of the player's board. It stays exhausted until the
+    /// start of the player's next turn.
+    ///
+    /// # Returns
+    /// A copy of the creature, as placed on the board, or `InvalidSummonPosition` if the slot
+    /// does not exist or is taken.
+    pub fn place_creature(
+        &mut self,
+        mut card: CardView,
+        position: &str,
+    ) -> Result<CardView, GameLogicError> {
+        let slot = self.free_creature_slot(position)?;
+        card.in_deck = false;
+        card.in_hand = false;
+        card.in_board = true;
+        card.is_exhausted = true;
+        card.position = Some(position.to_string());
+        Ok(self.board.creatures[slot].insert(card).clone())
+    }
+
+    /// Puts a card that never reached the board straight into the graveyard.
+    pub fn discard(&mut self, card: &CardView) {
+        self.graveyard.creatures.push(card.clone());
+        self.graveyard_size += 1;
+    }
+
+    /// Returns a `ManaChanged` event describing the player's current mana.
+    pub fn mana_event(&self) -> GameEvent {
+        GameEvent::ManaChanged {
+            player_id: self.id.clone(),
+            mana: self.mana,
+            max_mana: self.max_mana,
+            overload: self.overload,
         }
     }
+
+    /// Moves every creature whose health dropped to zero from the board to the graveyard.
+    ///
+    /// # Returns
+    /// The creatures that died, in board order.
+    pub fn remove_dead_creatures(&mut self) -> Vec<CardView> {
+        let mut dead = Vec::new();
+        for slot in self.board.creatures.iter_mut() {
+            if slot.as_ref().is_some_and(|card| card.health <= 0) {
+                if let Some(mut card) = slot.take() {
+                    card.in_board = false;
+                    card.in_graveyard = true;
+                    self.graveyard.creatures.push(card.clone());
+                    dead.push(card);
+                }
+            }
+        }
+
+        self.graveyard_size += dead.len();
+        dead
+    }
 }
 
-#[derive(Serialize, Clone)]
+#[derive(Serialize, Clone, JsonSchema)]
 pub struct PublicPlayerView {
     pub id: String,
     pub health: i32,
     pub mana: i32,
+    pub max_mana: i32,
+    pub locked_mana: i32,
+    pub overload: i32,
     pub hand_size: usize,
     pub deck_size: usize,
     pub graveyard_size: usize,
     pub board: BoardView,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn creature(id: &str, health: i32) -> CardView {
+        CardView {
+            id: id.to_string(),
+            card_id: "card".to_string(),
+            name: id.to_string(),
+            attack: 1,
+            health,
+            max_health: 1,
+            play_cost: 1,
+            owner_id: "player".to_string(),
+            effects: Vec::new(),
+            position: None,
+            in_deck: false,
+            in_hand: false,
+            in_board: true,
+            in_graveyard: false,
+            is_exhausted: false,
+        }
+    }
+
+    #[test]
+    fn test_remove_dead_creatures() {
+        let mut view = PlayerView::from_player("player", 0);
+        view.board.creatures[0] = Some(creature("alive", 1));
+        view.board.creatures[2] = Some(creature("dead", 0));
+
+        let dead = view.remove_dead_creatures();
+        assert_eq!(dead.len(), 1);
+        assert!(dead[0].in_graveyard);
+        assert!(view.board.creature("alive").is_some());
+        assert!(view.board.creature("dead").is_none());
+        assert_eq!(view.graveyard_size, 1);
+        assert_eq!(view.graveyard.creatures[0].id, "dead");
+    }
+
+    #[test]
+    fn test_add_to_hand() {
+        let mut view = PlayerView::from_player("player", 0);
+        for _ in 0..view.current_hand.len() {
+            assert!(view.add_to_hand(&creature("card", 1)).unwrap().in_hand);
+        }
+
+        assert_eq!(view.hand_size, 10);
+        assert!(view.add_to_hand(&creature("burned", 1)).is_none());
+    }
+
+    #[test]
+    fn test_play_from_hand() {
+        let mut view = PlayerView::from_player("player", 0);
+        view.add_to_hand(&creature("first", 1)).unwrap();
+        view.add_to_hand(&creature("second", 1)).unwrap();
+
+        // Copies of the same card are told apart by their instance id
+        let card = view.take_from_hand("second").unwrap();
+        assert_eq!(view.hand_size, 1);
+        assert!(view.take_from_hand("second").is_none());
+
+        let creature = view.place_creature(card, "3").unwrap();
+        assert!(creature.in_board && creature.is_exhausted);
+        assert!(view.board.creature("second").is_some());
+        assert!(view.free_creature_slot("3").is_err());
+        assert!(view.free_creature_slot("6").is_err());
+    }
+
+    #[test]
+    fn test_refill_mana() {
+        let mut view = PlayerView::from_player("player", 0);
+        view.refill_mana();
+        assert_eq!((view.mana, view.max_mana), (1, 1));
+        assert!(view.spend_mana(2).is_err());
+        assert!(view.spend_mana(1).is_ok());
+
+        // Overload is locked during the next turn only
+        view.overload = 1;
+        view.refill_mana();
+        assert_eq!((view.mana, view.max_mana, view.locked_mana), (1, 2, 1));
+        view.refill_mana();
+        assert_eq!((view.mana, view.locked_mana), (3, 0));
+
+        for _ in 0..20 {
+            view.refill_mana();
+        }
+        assert_eq!(view.max_mana, MAX_MANA);
+    }
+}
@@ -1,27 +1,53 @@
-use crate::game::entity::card::Card;
+use crate::game::entity::card::{Card, CardView};
+use crate::game::entity::deck::Deck;
 use crate::game::entity::player::{Player, PlayerView};
-use crate::game::game_state::GameState;
+use crate::game::game_state::{DrawOutcome, GameState, TurnPhase, OPENING_HAND_SIZE};
 use crate::game::lua_context::LuaContext;
 use crate::game::script_manager::ScriptManager;
 use crate::logger;
-use crate::models::client_requests::PlayCardRequest;
+use crate::models::client_requests::{
+    AttackRequest, ConcedeRequest, EndTurnRequest, MulliganRequest, PlayCardRequest,
+};
+use crate::models::game_event::GameEvent;
+use crate::models::http_response::PreloadedPlayer;
 use crate::models::init_server::PreloadPlayer;
+use crate::models::match_result::{MatchEndReason, MatchResult};
 use crate::tcp::client::Client;
 use crate::utils::errors::{GameInstanceError, GameLogicError};
 use crate::utils::logger::Logger;
+use crate::SETTINGS;
 use std::collections::HashMap;
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use std::time::Duration;
+use tokio::sync::{watch, RwLock};
+use tokio::time::sleep;
+use uuid::Uuid;
 
 pub struct GameInstance {
     pub game_state: Arc<RwLock<GameState>>, // The current game state, shared across tasks.
     pub script_manager: Arc<RwLock<ScriptManager>>, // The Lua script manager for handling game logic scripts.
     pub full_cards: Arc<RwLock<HashMap<String, Card>>>,
     pub connected_players: Arc<RwLock<HashMap<String, Arc<RwLock<Player>>>>>,
+    pub state_changed: watch::Sender<()>, // Wakes up the clients whenever the game state changes.
 }
 
 impl GameInstance {
-    pub async fn create_instance(players: Vec<PreloadPlayer>) -> Result<Self, GameInstanceError> {
+    /// Loads the scripts, players, decks and cards of a match, and shuffles each deck into
+    /// its owner's draw pile.
+    ///
+    /// The first player of the request plays red and the second one blue.
+    ///
+    /// # Arguments
+    /// * `players` - The players of the match and their selected decks.
+    /// * `seed` - Seeds the match's random decisions, random if `None`.
+    pub async fn create_instance(
+        players: Vec<PreloadPlayer>,
+        seed: Option<u64>,
+    ) -> Result<Self, GameInstanceError> {
+        if players.len() != 2 {
+            return Err(GameInstanceError::InvalidPlayerCount(players.len()));
+        }
+
         let mut lua_vm = ScriptManager::new_vm();
         lua_vm
             .load_scripts()
@@ -33,6 +59,8 @@ impl GameInstance {
         let mut full_cards_map: HashMap<String, Card> = HashMap::new();
         let mut connected_players: HashMap<String, Arc<RwLock<Player>>> = HashMap::new();
         let mut connect_players_views: HashMap<String, Arc<RwLock<PlayerView>>> = HashMap::new();
+        let mut player_ids: Vec<String> = Vec::new();
+        let mut decks: Vec<(PreloadedPlayer, Deck, Arc<RwLock<PlayerView>>)> = Vec::new();
 
         for player in &players {
             let player_profile = Player::preload_player_profile(&player.id)
@@ -51,111 +79,623 @@ impl GameInstance {
                 full_cards_map.insert(card.id.clone(), card);
             }
 
-            let deck_view = player_deck.create_view(&full_cards_map, &player_profile.id);
-            let player_view = Arc::new(RwLock::new(PlayerView::from_player(
-                &player_profile.id,
-                player_deck.cards.len(),
-            )));
-            
-            let player = Player::preload_player(player_profile, player_deck, deck_view, player_view.clone()).await;
+            let player_view = Arc::new(RwLock::new(PlayerView::from_player(&player_profile.id, 0)));
+
+            player_ids.push(player_profile.id.clone());
+            connect_players_views.insert(player_profile.id.clone(), player_view.clone());
+            decks.push((player_profile, player_deck, player_view));
+        }
+
+        let blue_player = player_ids.pop().unwrap_or_default();
+        let red_player = player_ids.pop().unwrap_or_default();
+        let seed = seed.unwrap_or_else(|| Uuid::new_v4().as_u64_pair().0);
+        let mut game_state =
+            GameState::new_game(connect_players_views, red_player, blue_player, seed);
+        logger!(INFO, "[GAME] Match seeded with `{}`", game_state.seed);
 
-            connect_players_views.insert(player.id.clone(), player_view);
-            connected_players.insert(player.id.clone(), Arc::new(RwLock::new(player)));
+        // Every physical card gets its instance id once the match state exists.
+        for (player_profile, player_deck, player_view) in decks {
+            let player_id = player_profile.id.clone();
+            let draw_pile = player_deck.draw_pile(&full_cards_map, |card| {
+                game_state.create_card(card, &player_id)
+            });
+            let deck_view = player_deck.create_view(&draw_pile);
+            game_state.build_draw_pile(&player_id, draw_pile).await;
+
+            let player =
+                Player::preload_player(player_profile, player_deck, deck_view, player_view).await;
+            connected_players.insert(player_id, Arc::new(RwLock::new(player)));
         }
 
         Ok(Self {
             script_manager: scripts,
             full_cards: Arc::new(RwLock::new(full_cards_map)),
             connected_players: Arc::new(RwLock::new(connected_players)),
-            game_state: Arc::new(RwLock::new(GameState::new_game(connect_players_views))),
+            game_state: Arc::new(RwLock::new(game_state)),
+            state_changed: watch::channel(()).0,
         })
     }
+
+    /// Tells every client that the game state has changed, so that each is sent its new view.
+    pub fn notify_change(&self) {
+        self.state_changed.send_replace(());
+    }
+}
+
+#[cfg(test)]
+impl GameInstance {
+    /// Creates a match between the players `red` and `blue`, with empty decks and no scripts
+    /// loaded, for tests.
+    pub fn for_tests() -> Self {
+        let mut player_views = HashMap::new();
+        let mut connected_players = HashMap::new();
+        for id in ["red", "blue"] {
+            let player_view = Arc::new(RwLock::new(PlayerView::from_player(id, 0)));
+            let deck = Deck {
+                id: format!("{id}-deck"),
+                player_id: id.to_string(),
+                name: id.to_string(),
+                cards: Vec::new(),
+            };
+            let player = Player {
+                id: id.to_string(),
+                level: 0,
+                username: id.to_string(),
+                deck_view: deck.create_view(&[]),
+                current_deck_id: deck.id.clone(),
+                current_deck: deck,
+                player_view: Arc::clone(&player_view),
+            };
+            player_views.insert(id.to_string(), player_view);
+            connected_players.insert(id.to_string(), Arc::new(RwLock::new(player)));
+        }
+
+        let game_state =
+            GameState::new_game(player_views, "red".to_string(), "blue".to_string(), 7);
+        Self {
+            game_state: Arc::new(RwLock::new(game_state)),
+            script_manager: Arc::new(RwLock::new(ScriptManager::new_vm())),
+            full_cards: Arc::new(RwLock::new(HashMap::new())),
+            connected_players: Arc::new(RwLock::new(connected_players)),
+            state_changed: watch::channel(()).0,
+        }
+    }
 }
 
 // Player Actions
 impl GameInstance {
+    /// Plays a card from the actor's hand, paying its cost and running its `on_play` triggers.
+    ///
+    /// The card is placed on the actor's board, in the free slot given by `target_position`.
+    ///
+    /// Once the card is on the board, failing triggers are logged and skipped.
+    ///
+    /// # Returns
+    /// * `Ok(Vec<GameEvent>)` - The events caused by the card's triggers.
+    /// * `Err(GameLogicError)` - If the card cannot be played.
     pub async fn play_card(
         self: Arc<Self>,
         client: Arc<Client>,
         request: &PlayCardRequest,
-    ) -> Result<(), GameLogicError> {
-        let game_state = self.game_state.read().await;
-        let player_views = game_state.player_views.read().await;
+    ) -> Result<Vec<GameEvent>, GameLogicError> {
+        // Every guard is released before running the triggers, which update the player views.
+        let card_view = {
+            let game_state = self.game_state.read().await;
+            let player_views = game_state.player_views.read().await;
+
+            // Clone and lock the Client player object to compare identity and access full player data.
+            let player_clone = Arc::clone(&client.player);
+            let player_guard = player_clone.read().await;
+
+            // Try to fetch the PrivatePlayerView for the given player ID. Return an error if not found.
+            let player_view = player_views.get(&request.actor_id).ok_or_else(|| {
+                logger!(DEBUG, "[PLAY CARD] Play card actor: {}", &request.actor_id);
+                logger!(DEBUG, "[PLAY CARD] Play card client: {}", &player_guard.id);
+                return GameLogicError::PlayerNotFound;
+            })?;
+
+            let player_view_clone = Arc::clone(player_view);
+            let player_view_guard = player_view_clone.read().await;
+
+            // Ensure that the client attempting the action matches the player in the request.
+            if &player_guard.id != &player_view_guard.id {
+                return Err(GameLogicError::PlayerIdDoesNotMatch);
+            }
+
+            //Confirm it is currently this player's turn.
+            game_state.ensure_active(&request.actor_id)?;
+
+            // Verifies if the card played is actually in the player's hand. This does not account for
+            // out-of-hand plays from special interactions as they do not exist yet.
+            let player_hand = player_view_guard.current_hand.iter();
+            player_hand
+                .flatten()
+                .find(|c| c.id == request.instance_id)
+                .ok_or_else(|| GameLogicError::CardPlayedIsNotInHand)?
+                .clone()
+        };
+
+        // Retrieve the full card details from game_cards. If not present, fetch it from external storage and add it to the shared card list.
+        if !self.full_cards.read().await.contains_key(&card_view.card_id) {
+            let card = Card::request_card(&card_view.card_id)
+                .await
+                .map_err(|_| GameLogicError::UnableToGetCardDetails)?;
+            self.add_card(card).await;
+        }
+
+        // Take the card from the hand and pay its cost, then move it to the board. The card is
+        // put back if the player cannot afford it, so a rejected play leaves the view unchanged.
+        let (card_view, mut events) = {
+            let position = request.target_position.as_deref().unwrap_or_default();
+            let (player_view, _) = self.player_and_opponent(&request.actor_id).await?;
+            let mut player_view_guard = player_view.write().await;
+
+            let slot = player_view_guard.free_creature_slot(position)?;
+            let card = player_view_guard
+                .take_from_hand(&card_view.id)
+                .ok_or(GameLogicError::CardPlayedIsNotInHand)?;
+            if let Err(error) = player_view_guard.spend_mana(card.play_cost) {
+                player_view_guard.add_to_hand(&card);
+                return Err(error);
+            }
+            let creature = player_view_guard.place_creature(card, position)?;
+
+            let events = vec![
+                player_view_guard.mana_event(),
+                GameEvent::Summoned {
+                    instance_id: creature.id.clone(),
+                    card_id: creature.card_id.clone(),
+                    owner_id: creature.owner_id.clone(),
+                    position: slot,
+                },
+            ];
+            (creature, events)
+        };
+
+        // Run the card’s on_play triggers. The card is played by now, so failing triggers are
+        // skipped rather than rejecting the play.
+        let on_play = self.card_hooks(&card_view.card_id, |card| &card.on_play).await;
+        events.extend(self.run_hooks(&on_play, &card_view, None, "on_play").await);
+        self.notify_change();
+        Ok(events)
+    }
+
+    /// Attacks an enemy creature or hero with one of the actor's creatures.
+    ///
+    /// - Validates that the attacker is the actor's creature on the board and is not exhausted.
+    /// - Validates that the target is a creature on the enemy board, or the enemy hero.
+    /// - Fires the attacker's `on_attack` triggers.
+    /// - Exchanges damage: creatures deal their attack to each other, heroes do not strike back.
+    /// - Fires the target's `on_hit` triggers, then moves dead creatures to the graveyard.
+    ///
+    /// The attacker is exhausted until it is refreshed. Once the attack is validated, failing
+    /// triggers are logged and skipped, and the attack fizzles if a trigger removed either
+    /// creature from the board.
+    ///
+    /// # Arguments
+    /// * `client` - The client attempting the attack.
+    /// * `request` - The attack request.
+    ///
+    /// # Returns
+    /// * `Ok(Vec<GameEvent>)` - The events caused by the attack and its triggers.
+    /// * `Err(GameLogicError)` - If the attacker or the target is invalid.
+    pub async fn attack(
+        self: Arc<Self>,
+        client: Arc<Client>,
+        request: &AttackRequest,
+    ) -> Result<Vec<GameEvent>, GameLogicError> {
+        if client.player.read().await.id != request.actor_id {
+            return Err(GameLogicError::PlayerIdDoesNotMatch);
+        }
+        let (attacker, target) = {
+            let game_state = self.game_state.read().await;
+            game_state.ensure_active(&request.actor_id)?;
+            game_state
+                .attack_target(&request.actor_id, &request.attacker_id, &request.target_id)
+                .await?
+        };
 
-        // Clone and lock the Client player object to compare identity and access full player data.
-        let player_clone = Arc::clone(&client.player);
-        let player_guard = player_clone.read().await;
+        let on_attack = self.card_hooks(&attacker.card_id, |card| &card.on_attack).await;
+        let mut events = self
+            .run_hooks(&on_attack, &attacker, target.clone(), "on_attack")
+            .await;
 
-        // Try to fetch the PrivatePlayerView for the given player ID. Return an error if not found.
-        let player_view = player_views.get(&request.actor_id).ok_or_else(|| {
-            logger!(DEBUG, "[PLAY CARD] Play card actor: {}", &request.actor_id);
-            logger!(DEBUG, "[PLAY CARD] Play card client: {}", &player_guard.id);
-            return GameLogicError::PlayerNotFound;
-        })?;
+        // Triggers may have changed the board, so both creatures are looked up again. The
+        // attack fizzles if either of them has left it.
+        let target_id = target.as_ref().map(|target| target.id.as_str());
+        let exchange = self
+            .game_state
+            .read()
+            .await
+            .exchange_damage(&request.actor_id, &request.attacker_id, target_id)
+            .await;
+        match exchange {
+            Ok((damage_events, attacker, target)) => {
+                events.extend(damage_events);
+                if let Some(target) = &target {
+                    let on_hit = self.card_hooks(&target.card_id, |card| &card.on_hit).await;
+                    let hit_events = self
+                        .run_hooks(&on_hit, target, Some(attacker), "on_hit")
+                        .await;
+                    events.extend(hit_events);
+                }
+            }
+            Err(error) => logger!(
+                INFO,
+                "[GAME] Attack of `{}` fizzled: {error}",
+                request.attacker_id
+            ),
+        }
 
-        let player_view_clone = Arc::clone(player_view);
-        let player_view_guard = player_view_clone.read().await;
+        events.extend(self.game_state.read().await.resolve_deaths().await);
+        self.notify_change();
+        Ok(events)
+    }
+}
 
-        // Ensure that the client attempting the action matches the player in the request.
-        if &player_guard.id != &player_view_guard.id {
+// Turn implementations
+impl GameInstance {
+    /// Deals both opening hands and opens the mulligan phase, unless the match has already
+    /// begun.
+    ///
+    /// The first turn starts once both players have answered their mulligan, or after
+    /// `MULLIGAN_TIMEOUT_MS`. Opening hands do not fire `on_draw` triggers.
+    pub async fn start_game(self: Arc<Self>) {
+        {
+            let mut game_state = self.game_state.write().await;
+            if game_state.started() || game_state.phase == TurnPhase::Mulligan {
+                return;
+            }
+
+            let players = [game_state.red_player.clone(), game_state.blue_player.clone()];
+            for player_id in &players {
+                for _ in 0..OPENING_HAND_SIZE {
+                    if let Err(error) = game_state.draw_card(player_id).await {
+                        logger!(ERROR, "[GAME] Unable to deal `{player_id}`'s hand: {error}");
+                    }
+                }
+            }
+            game_state.phase = TurnPhase::Mulligan;
+            game_state.pending_mulligans = players.into_iter().collect();
+        }
+        self.notify_change();
+
+        let settings = SETTINGS.get().expect("Settings not initialized");
+        let timeout = Duration::from_millis(settings.mulligan_timeout_ms);
+        logger!(INFO, "[GAME] Mulligan phase started, ending in {timeout:?} at the latest");
+
+        tokio::spawn(async move {
+            sleep(timeout).await;
+            self.finish_mulligan().await;
+        });
+    }
+
+    /// Returns cards from the actor's opening hand and draws their replacements. The first
+    /// turn starts as soon as both players have answered.
+    ///
+    /// # Arguments
+    /// * `client` - The client sending their mulligan.
+    /// * `request` - The mulligan request.
+    ///
+    /// # Returns
+    /// * `Ok(Vec<GameEvent>)` - The replacement draws, followed by the events of the first
+    ///   turn's start if this was the last answer.
+    /// * `Err(GameLogicError)` - If the player may not mulligan or a card is not in their hand.
+    pub async fn mulligan(
+        self: Arc<Self>,
+        client: Arc<Client>,
+        request: &MulliganRequest,
+    ) -> Result<Vec<GameEvent>, GameLogicError> {
+        if client.player.read().await.id != request.actor_id {
             return Err(GameLogicError::PlayerIdDoesNotMatch);
         }
 
-        //Confirm it is currently this player's turn.
-        if &player_view_guard.id != &request.actor_id {
-            return Err(GameLogicError::NotPlayerTurn);
+        let (mut events, everyone_answered) = {
+            let mut game_state = self.game_state.write().await;
+            let events = game_state
+                .mulligan(&request.actor_id, &request.instance_ids)
+                .await?;
+            (events, game_state.pending_mulligans.is_empty())
+        };
+        self.notify_change();
+
+        if everyone_answered {
+            events.extend(self.finish_mulligan().await);
         }
+        Ok(events)
+    }
 
-        // Verifies if the card played is actually in the player's hand. This does not account for
-        // out-of-hand plays from special interactions as they do not exist yet.
-        let player_hand = player_view_guard.current_hand.iter();
-        let card_view = player_hand
-            .flatten()
-            .find(|c| c.id == request.card_id)
-            .ok_or_else(|| GameLogicError::CardPlayedIsNotInHand)?;
+    /// Closes the mulligan phase, keeping the hands of the players who did not answer, and
+    /// starts the first turn. Does nothing outside of the mulligan phase, or once the match
+    /// is over.
+    ///
+    /// # Returns
+    /// The events caused by the start of the first turn.
+    async fn finish_mulligan(&self) -> Vec<GameEvent> {
+        let first_player = {
+            let mut game_state = self.game_state.write().await;
+            if game_state.phase != TurnPhase::Mulligan || game_state.result.is_some() {
+                return Vec::new();
+            }
 
-        // Verify that the requested card is in the player's current hand.
-        // Retrieve the full card details from game_cards. If not present, fetch it from external storage and add it to the shared card list.
-        let game_cards_lock = self.full_cards.read().await;
-        let full_card = match game_cards_lock.get(&card_view.id) {
-            Some(card) => card,
-            None => {
-                let card = Card::request_card(&card_view.id)
-                    .await
-                    .map_err(|_| GameLogicError::UnableToGetCardDetails)?;
-                self.add_card(card).await;
-                game_cards_lock.get(&card_view.id).ok_or_else(|| {
-                    return GameLogicError::UnableToGetCardDetails;
-                })?
+            for player_id in game_state.pending_mulligans.drain() {
+                logger!(INFO, "[GAME] `{player_id}` did not mulligan in time, keeping their hand");
             }
+            game_state.phase = TurnPhase::Start;
+            game_state.first_player().to_string()
+        };
+
+        logger!(INFO, "[GAME] Match started, `{first_player}` plays first");
+        self.start_turn(&first_player).await
+    }
+
+    /// Ends the actor's turn and starts their opponent's.
+    ///
+    /// # Arguments
+    /// * `client` - The client ending their turn.
+    /// * `request` - The end turn request.
+    ///
+    /// # Returns
+    /// * `Ok(Vec<GameEvent>)` - The events caused by the end and start of turn triggers.
+    /// * `Err(GameLogicError)` - If it is not the actor's turn.
+    pub async fn end_turn(
+        self: Arc<Self>,
+        client: Arc<Client>,
+        request: &EndTurnRequest,
+    ) -> Result<Vec<GameEvent>, GameLogicError> {
+        if client.player.read().await.id != request.actor_id {
+            return Err(GameLogicError::PlayerIdDoesNotMatch);
+        }
+
+        let opponent = {
+            let mut game_state = self.game_state.write().await;
+            game_state.ensure_active(&request.actor_id)?;
+            game_state.phase = TurnPhase::End;
+            game_state.opponent_of(&request.actor_id).to_string()
         };
 
-        // Iterate over the card’s on_play triggers, creating a Lua execution context for each.
-        for action in &full_card.on_play {
-            let lua_context = LuaContext::new(
-                Arc::clone(&self.game_state),
-                card_view,
-                None,
-                "on_play".to_string(),
-                action.to_string(),
-            )
+        let mut events = self
+            .run_board_hooks(&request.actor_id, |card| &card.on_turn_end, "on_turn_end")
             .await;
+        events.extend(self.start_turn(&opponent).await);
+        Ok(events)
+    }
 
-            // Execute each script action using the ScriptManager and apply the resulting game actions to the state.
-            let script_manager_guard = self.script_manager.read().await;
-            let game_actions = script_manager_guard
-                .call_function_ctx(action, lua_context)
-                .await?;
+    /// Gives up the match on behalf of the actor, whose opponent wins.
+    ///
+    /// # Arguments
+    /// * `client` - The client conceding.
+    /// * `request` - The concede request.
+    ///
+    /// # Returns
+    /// * `Ok(Vec<GameEvent>)` - No events, the result is announced with `MatchEnded`.
+    /// * `Err(GameLogicError)` - If the actor is not the client's player or the match is over.
+    pub async fn concede(
+        self: Arc<Self>,
+        client: Arc<Client>,
+        request: &ConcedeRequest,
+    ) -> Result<Vec<GameEvent>, GameLogicError> {
+        if client.player.read().await.id != request.actor_id {
+            return Err(GameLogicError::PlayerIdDoesNotMatch);
+        }
 
-            game_state.apply_actions(game_actions).await;
+        let mut game_state = self.game_state.write().await;
+        if game_state.result.is_some() {
+            return Err(GameLogicError::MatchOver);
         }
 
-        Ok(())
+        logger!(INFO, "[GAME] `{}` conceded", request.actor_id);
+        game_state.lose(&request.actor_id, MatchEndReason::Conceded);
+        self.notify_change();
+        Ok(Vec::new())
+    }
+
+    /// Returns the result of the match once it is over, checking whether a hero has fallen.
+    pub async fn match_result(&self) -> Option<MatchResult> {
+        self.game_state.write().await.check_heroes().await
+    }
+
+    /// Starts the given player's turn: their mana grows and is refilled, their creatures are
+    /// refreshed, they draw a card, their `on_turn_start` triggers run, then the turn moves
+    /// to its main phase.
+    ///
+    /// # Returns
+    /// The player's new mana, followed by the events caused by the draw and the start of
+    /// turn triggers.
+    async fn start_turn(&self, player_id: &str) -> Vec<GameEvent> {
+        let player_view = {
+            let mut game_state = self.game_state.write().await;
+            game_state.rounds += 1;
+            game_state.phase = TurnPhase::Start;
+            game_state.active_player = player_id.to_string();
+            let player_view = game_state.player_views.read().await.get(player_id).cloned();
+            player_view
+        };
+
+        let mut events = Vec::new();
+        if let Some(player_view) = player_view {
+            let mut player_view_guard = player_view.write().await;
+            player_view_guard.refill_mana();
+            events.push(player_view_guard.mana_event());
+            for creature in player_view_guard.board.creatures.iter_mut().flatten() {
+                creature.is_exhausted = false;
+            }
+        }
+
+        events.extend(self.draw_cards(player_id, 1).await);
+        events.extend(
+            self.run_board_hooks(player_id, |card| &card.on_turn_start, "on_turn_start")
+                .await,
+        );
+        self.game_state.write().await.phase = TurnPhase::Main;
+        self.notify_change();
+        events
+    }
+}
+
+// Scripting implementations
+impl GameInstance {
+    /// Runs a card's triggers for an event, applying the resulting game actions to the state.
+    ///
+    /// Actions are applied on behalf of the actor's owner. Failing triggers are logged and
+    /// skipped, leaving the state as it was before them, so that they cannot stall the game.
+    ///
+    /// # Arguments
+    /// * `hooks` - The Lua functions to call, in order.
+    /// * `actor` - The card whose triggers are run.
+    /// * `target` - The card the event is aimed at, if any.
+    /// * `event` - The name of the event (e.g., `on_play`).
+    ///
+    /// # Returns
+    /// The events caused by the triggers that succeeded.
+    async fn run_hooks(
+        &self,
+        hooks: &[String],
+        actor: &CardView,
+        target: Option<CardView>,
+        event: &str,
+    ) -> Vec<GameEvent> {
+        let mut events = Vec::new();
+        for action in hooks {
+            match self.run_hook(action, actor, target.clone(), event).await {
+                Ok(hook_events) => events.extend(hook_events),
+                Err(error) => logger!(
+                    ERROR,
+                    "[GAME] `{event}` trigger `{action}` of `{}` failed: {error}",
+                    actor.id
+                ),
+            }
+        }
+
+        events
+    }
+
+    /// Runs a single trigger of a card, applying the resulting game actions to the state.
+    ///
+    /// # Returns
+    /// * `Ok(Vec<GameEvent>)` - The events caused by the trigger's actions.
+    /// * `Err(GameLogicError)` - If the trigger or one of its actions failed, in which case
+    ///   none of its actions are applied.
+    async fn run_hook(
+        &self,
+        action: &str,
+        actor: &CardView,
+        target: Option<CardView>,
+        event: &str,
+    ) -> Result<Vec<GameEvent>, GameLogicError> {
+        let lua_context = LuaContext::new(
+            Arc::clone(&self.game_state),
+            actor,
+            target,
+            event.to_string(),
+            action.to_string(),
+        )
+        .await;
+
+        let game_actions = self
+            .script_manager
+            .read()
+            .await
+            .call_function_ctx(action, lua_context)
+            .await?;
+        let cards = self.full_cards.read().await;
+        let game_state = self.game_state.read().await;
+        game_state
+            .apply_actions(game_actions, &actor.owner_id, &cards)
+            .await
+    }
+
+    /// Runs one trigger of every creature on a player's board.
+    ///
+    /// # Arguments
+    /// * `player_id` - The owner of the board.
+    /// * `hooks` - Selects the trigger list of a card.
+    /// * `event` - The name of the event (e.g., `on_turn_start`).
+    async fn run_board_hooks(
+        &self,
+        player_id: &str,
+        hooks: fn(&Card) -> &Vec<String>,
+        event: &str,
+    ) -> Vec<GameEvent> {
+        let creatures: Vec<CardView> = match self.player_and_opponent(player_id).await {
+            Ok((player_view, _)) => {
+                let player_view_guard = player_view.read().await;
+                player_view_guard.board.creatures.iter().flatten().cloned().collect()
+            }
+            Err(_) => Vec::new(),
+        };
+
+        let mut events = Vec::new();
+        for creature in &creatures {
+            let creature_hooks = self.card_hooks(&creature.card_id, hooks).await;
+            events.extend(self.run_hooks(&creature_hooks, creature, None, event).await);
+        }
+
+        events
+    }
+
+    /// Returns a copy of one of a catalog card's trigger lists, empty if the card is unknown.
+    async fn card_hooks(&self, card_id: &str, hooks: fn(&Card) -> &Vec<String>) -> Vec<String> {
+        match self.full_cards.read().await.get(card_id) {
+            Some(card) => hooks(card).clone(),
+            None => Vec::new(),
+        }
     }
 }
 
 // Card implementations
 impl GameInstance {
+    /// Draws cards from the top of a player's draw pile, running the `on_draw` triggers of
+    /// each card that reaches their hand.
+    ///
+    /// Cards drawn into a full hand are burned. Failing triggers are logged and skipped.
+    /// Drawing from an empty deck deals fatigue damage instead.
+    ///
+    /// # Arguments
+    /// * `player_id` - The player drawing.
+    /// * `count` - How many cards to draw.
+    ///
+    /// # Returns
+    /// The events caused by the draws and their triggers.
+    pub async fn draw_cards(&self, player_id: &str, count: usize) -> Vec<GameEvent> {
+        let mut events = Vec::new();
+        for _ in 0..count {
+            let outcome = self.game_state.write().await.draw_card(player_id).await;
+            let card = match outcome {
+                Ok(DrawOutcome::Drawn(card)) => card,
+                Ok(DrawOutcome::Burned(card)) => {
+                    events.push(GameEvent::Burned {
+                        instance_id: card.id,
+                        card_id: card.card_id,
+                        owner_id: card.owner_id,
+                    });
+                    continue;
+                }
+                Ok(DrawOutcome::Fatigue { damage, health }) => {
+                    logger!(INFO, "[GAME] `{player_id}` takes {damage} fatigue damage");
+                    events.push(GameEvent::Fatigued {
+                        player_id: player_id.to_string(),
+                        damage,
+                        health,
+                    });
+                    continue;
+                }
+                Err(error) => {
+                    logger!(ERROR, "[GAME] `{player_id}` was unable to draw: {error}");
+                    break;
+                }
+            };
+
+            events.push(GameEvent::Drew {
+                owner_id: card.owner_id.clone(),
+            });
+            let on_draw = self.card_hooks(&card.card_id, |card| &card.on_draw).await;
+            events.extend(self.run_hooks(&on_draw, &card, None, "on_draw").await);
+        }
+
+        events
+    }
+
     /// Store a card in the game state.
     pub async fn add_card(&self, card: Card) {
         let mut card_vec = self.full_cards.write().await;
@@ -165,6 +705,18 @@ impl GameInstance {
 
 // Player implementations
 impl GameInstance {
+    /// Returns the views of the given player and of their opponent.
+    async fn player_and_opponent(
+        &self,
+        player_id: &str,
+    ) -> Result<(Arc<RwLock<PlayerView>>, Arc<RwLock<PlayerView>>), GameLogicError> {
+        self.game_state
+            .read()
+            .await
+            .player_and_opponent(player_id)
+            .await
+    }
+
     // pub async fn add_player(&mut self, player: Arc<Player>) {
     //     let player_view = PlayerView::from_player(player.clone());
     //     let player_view_guard = Arc::new(RwLock::new(player_view));
@@ -183,3 +735,158 @@ impl GameInstance {
     //     player_views_guard.insert(player.id.clone(), player_view_guard);
     // }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::client_requests::PlayCardRequest;
+    use crate::tcp::handshake::Capabilities;
+    use crate::tcp::header::HeaderType;
+    use crate::tcp::protocol::Protocol;
+    use crate::tcp::transport;
+
+    /// Triggers dealing one damage to a hero, registered as `triggers:hit_red` and
+    /// `triggers:hit_blue`.
+    const HIT_TRIGGERS: &str = r#"
+        function hit_red() return { { type = "DealDamage", target = "red", amount = 1 } } end
+        function hit_blue() return { { type = "DealDamage", target = "blue", amount = 1 } } end
+    "#;
+
+    fn creature(id: &str, on_turn_start: &[&str], on_turn_end: &[&str]) -> Card {
+        let hooks = |names: &[&str]| names.iter().map(|name| name.to_string()).collect();
+        Card {
+            id: id.to_string(),
+            name: id.to_string(),
+            description: String::new(),
+            play_cost: 1,
+            attack: 1,
+            health: 1,
+            rarity: 0,
+            on_play: Vec::new(),
+            on_draw: Vec::new(),
+            on_attack: Vec::new(),
+            on_hit: Vec::new(),
+            on_turn_start: hooks(on_turn_start),
+            on_turn_end: hooks(on_turn_end),
+            on_death: Vec::new(),
+            on_ally_death: Vec::new(),
+            on_enemy_death: Vec::new(),
+        }
+    }
+
+    /// Places a copy of the card on the player's board, and gives them two more to draw.
+    async fn prepare(game_instance: &GameInstance, owner_id: &str, card: Card) {
+        {
+            let mut game_state = game_instance.game_state.write().await;
+            let pile: Vec<CardView> = (0..2)
+                .map(|_| game_state.create_card(&card, owner_id))
+                .collect();
+            game_state.build_draw_pile(owner_id, pile).await;
+
+            let creature = game_state.create_card(&card, owner_id);
+            let (player_view, _) = game_state.player_and_opponent(owner_id).await.unwrap();
+            let mut player_view_guard = player_view.write().await;
+            player_view_guard.place_creature(creature, "0").unwrap();
+        }
+        game_instance.add_card(card).await;
+    }
+
+    #[tokio::test]
+    async fn test_end_turn() {
+        let protocol = Arc::new(Protocol::for_tests().await);
+        let game_instance = Arc::clone(&protocol.game_instance);
+        {
+            let script_manager = game_instance.script_manager.read().await;
+            script_manager.lua.load(HIT_TRIGGERS).exec().unwrap();
+            for name in ["hit_red", "hit_blue"] {
+                let function = script_manager.lua.globals().get(name).unwrap();
+                let mut triggers = script_manager.triggers.lock().await;
+                triggers.insert(name.to_string(), function);
+            }
+        }
+
+        // Red's creature hits blue as red's turn ends, blue's hits red as blue's turn starts
+        let red_creature = creature("red_creature", &[], &["triggers:hit_blue"]);
+        let blue_creature = creature("blue_creature", &["triggers:hit_red"], &[]);
+        prepare(&game_instance, "red", red_creature).await;
+        prepare(&game_instance, "blue", blue_creature).await;
+        game_instance.start_turn("red").await;
+
+        let (red_client, _red_remote) =
+            Client::for_tests(&protocol, "red", Capabilities::default()).await;
+        let (blue_client, mut blue_remote) =
+            Client::for_tests(&protocol, "blue", Capabilities::default()).await;
+        tokio::spawn(blue_client.connect());
+
+        let request = EndTurnRequest {
+            request_id: 1,
+            actor_id: "red".to_string(),
+        };
+        let events = Arc::clone(&game_instance)
+            .end_turn(red_client, &request)
+            .await
+            .unwrap();
+        let damaged: Vec<&str> = events
+            .iter()
+            .filter_map(|event| match event {
+                GameEvent::Damaged { target, .. } => Some(target.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(damaged, ["blue", "red"]);
+
+        let expected = {
+            let game_state = game_instance.game_state.read().await;
+            assert_eq!(game_state.active_player, "blue");
+            assert_eq!(game_state.phase, TurnPhase::Main);
+            assert_eq!(game_state.rounds, 2);
+            game_state.wrap_game_state("blue").await.unwrap()
+        };
+
+        // The new active player is sent the state of their turn
+        loop {
+            let packet = transport::receive(&mut blue_remote, HeaderType::GameState).await;
+            if packet.payload[..] == expected[..] {
+                break;
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_unaffordable_play_keeps_card() {
+        let protocol = Arc::new(Protocol::for_tests().await);
+        let game_instance = Arc::clone(&protocol.game_instance);
+        prepare(&game_instance, "red", creature("red_creature", &[], &[])).await;
+        game_instance.start_turn("red").await;
+
+        let (player_view, _) = game_instance.player_and_opponent("red").await.unwrap();
+        let instance_id = {
+            let mut player_view_guard = player_view.write().await;
+            player_view_guard.mana = 0;
+            let card = player_view_guard.current_hand.iter().flatten().next();
+            card.unwrap().id.clone()
+        };
+
+        let (red_client, _red_remote) =
+            Client::for_tests(&protocol, "red", Capabilities::default()).await;
+        let request = PlayCardRequest {
+            request_id: 1,
+            actor_id: "red".to_string(),
+            instance_id: instance_id.clone(),
+            target_id: None,
+            target_position: Some("1".to_string()),
+        };
+        let result = Arc::clone(&game_instance)
+            .play_card(red_client, &request)
+            .await;
+        assert!(matches!(result, Err(GameLogicError::NotEnoughMana(1, 0))));
+
+        // The card is back in the hand and nothing was spent or summoned
+        let player_view_guard = player_view.read().await;
+        let mut hand = player_view_guard.current_hand.iter().flatten();
+        assert!(hand.any(|card| card.id == instance_id && card.in_hand));
+        assert_eq!(player_view_guard.hand_size, 1);
+        assert_eq!(player_view_guard.mana, 0);
+        assert!(player_view_guard.board.creatures[1].is_none());
+    }
+}
@@ -1,10 +1,18 @@
-use crate::game::entity::card::{Card, CardRef};
-use crate::game::entity::player::{Player, PlayerView, PublicPlayerView};
+use crate::game::entity::card::{Card, CardRef, CardView};
+use crate::game::entity::player::{Player, PlayerView, PublicPlayerView, MAX_MANA};
 use crate::logger;
 use crate::models::game_action::GameAction;
+use crate::models::game_event::GameEvent;
+use crate::models::match_result::{MatchEndReason, MatchResult};
 use crate::utils::errors::{CardRequestError, GameLogicError};
 use crate::utils::logger::Logger;
+use std::collections::HashSet;
 use std::{collections::HashMap, sync::Arc};
+use std::sync::atomic::{AtomicU32, Ordering};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use schemars::JsonSchema;
 use serde::Serialize;
 use tokio::sync::RwLock;
 use crate::game::lua_context::LuaContext;
@@ -12,45 +20,1081 @@ use crate::models::client_requests::PlayCardRequest;
 use crate::tcp::client::Client;
 use crate::tcp::server::ServerInstance;
 
+/// Step of the active player's turn.
+///
+/// - `Mulligan` - Before the first turn, both players may replace part of their opening hand.
+/// - `Start` - Creatures are refreshed and `on_turn_start` triggers run.
+/// - `Main` - The active player may play cards, attack and end their turn.
+/// - `End` - `on_turn_end` triggers run before the turn passes to the other player.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Default, JsonSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum TurnPhase {
+    Mulligan,
+    #[default]
+    Start,
+    Main,
+    End,
+}
+
+/// Number of cards each player draws before the first turn.
+pub const OPENING_HAND_SIZE: usize = 3;
+
+/// Result of drawing the top card of a player's draw pile.
+pub enum DrawOutcome {
+    Drawn(CardView),                      // The card, as placed in the player's hand.
+    Burned(CardView),                     // The hand was full, so the card went to the graveyard.
+    Fatigue { damage: u32, health: i32 }, // The deck was empty, so the hero took fatigue.
+}
+
 pub struct GameState {
-    pub rounds: u32,
+    pub rounds: u32, // Number of turns started so far, `0` until the match begins.
     pub red_first: bool,
     pub red_player: String,
     pub blue_player: String,
+    pub active_player: String, // The player whose turn it is, empty until the match begins.
+    pub phase: TurnPhase,
     pub ongoing: Arc<RwLock<bool>>,
-    pub player_views: Arc<RwLock<HashMap<String, Arc<RwLock<PlayerView>>>>>
+    pub player_views: Arc<RwLock<HashMap<String, Arc<RwLock<PlayerView>>>>>,
+    pub seed: u64,   // Seed of `rng`, logged so that a match can be replayed.
+    pub rng: StdRng, // Source of every random decision of the match.
+    pub draw_piles: HashMap<String, Vec<CardView>>, // Shuffled decks, the top card last.
+    next_instance: AtomicU32, // Instance id of the next physical card created.
+    pub pending_mulligans: HashSet<String>, // Players who may still replace part of their hand.
+    pub result: Option<MatchResult>, // Set once the match is over.
+    pub fatigue: HashMap<String, u32>, // Draws each player attempted from an empty deck.
 }
 
 impl GameState {
-    pub fn new_game(views: HashMap<String, Arc<RwLock<PlayerView>>>) -> Self {
+    pub fn new_game(
+        views: HashMap<String, Arc<RwLock<PlayerView>>>,
+        red_player: String,
+        blue_player: String,
+        seed: u64,
+    ) -> Self {
         Self {
             rounds: 0,
             red_first: true,
-            red_player: String::new(),
-            blue_player: String::new(),
+            red_player,
+            blue_player,
+            active_player: String::new(),
+            phase: TurnPhase::default(),
             player_views: Arc::new(RwLock::new(views)),
             ongoing: Arc::new(RwLock::new(true)),
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+            draw_piles: HashMap::new(),
+            next_instance: AtomicU32::new(1),
+            pending_mulligans: HashSet::new(),
+            result: None,
+            fatigue: HashMap::new(),
+        }
+    }
+
+    /// Creates a new physical card of the match, with its own instance id.
+    ///
+    /// Instance ids are allocated in creation order, so replaying a match with the same seed
+    /// gives every card the same id.
+    ///
+    /// # Arguments
+    /// * `card` - The catalog card it is a copy of.
+    /// * `owner_id` - The player who owns the card.
+    pub fn create_card(&self, card: &Card, owner_id: &str) -> CardView {
+        let instance = self.next_instance.fetch_add(1, Ordering::Relaxed);
+        CardView::create_view(card, format!("card-{instance}"), owner_id.to_string())
+    }
+
+    /// Shuffles a player's cards into their draw pile, replacing any previous one.
+    ///
+    /// # Arguments
+    /// * `player_id` - The owner of the cards.
+    /// * `cards` - Every copy of the cards in the player's deck.
+    pub async fn build_draw_pile(&mut self, player_id: &str, mut cards: Vec<CardView>) {
+        cards.shuffle(&mut self.rng);
+        if let Some(view) = self.player_views.read().await.get(player_id) {
+            view.write().await.deck_size = cards.len();
+        }
+        self.draw_piles.insert(player_id.to_string(), cards);
+    }
+
+    /// Draws the top card of a player's draw pile into their hand.
+    ///
+    /// Drawing from an empty deck raises the player's fatigue by one instead, and deals that
+    /// much damage to their hero.
+    ///
+    /// # Returns
+    /// * `Ok(DrawOutcome)` - Where the card went, or the fatigue taken if there was none left.
+    /// * `Err(GameLogicError::PlayerNotFound)` - If the player is not part of the match.
+    pub async fn draw_card(&mut self, player_id: &str) -> Result<DrawOutcome, GameLogicError> {
+        let view = self
+            .player_views
+            .read()
+            .await
+            .get(player_id)
+            .cloned()
+            .ok_or(GameLogicError::PlayerNotFound)?;
+        let Some(card) = self.draw_piles.get_mut(player_id).and_then(Vec::pop) else {
+            let fatigue = self.fatigue.entry(player_id.to_string()).or_default();
+            *fatigue += 1;
+            let mut view_guard = view.write().await;
+            view_guard.health = (view_guard.health - to_points(*fatigue)).max(0);
+            return Ok(DrawOutcome::Fatigue {
+                damage: *fatigue,
+                health: view_guard.health,
+            });
+        };
+
+        let mut view_guard = view.write().await;
+        view_guard.deck_size = view_guard.deck_size.saturating_sub(1);
+        match view_guard.add_to_hand(&card) {
+            Some(card) => Ok(DrawOutcome::Drawn(card)),
+            None => {
+                let mut card = card;
+                card.in_deck = false;
+                card.in_graveyard = true;
+                view_guard.discard(&card);
+                Ok(DrawOutcome::Burned(card))
+            }
+        }
+    }
+
+    /// Whether the match has begun.
+    pub fn started(&self) -> bool {
+        self.rounds > 0
+    }
+
+    /// Returns the player who opens the match.
+    pub fn first_player(&self) -> &str {
+        match self.red_first {
+            true => &self.red_player,
+            false => &self.blue_player,
+        }
+    }
+
+    /// Returns the opponent of the given player.
+    pub fn opponent_of(&self, player_id: &str) -> &str {
+        match player_id == self.red_player {
+            true => &self.blue_player,
+            false => &self.red_player,
+        }
+    }
+
+    /// Ensures the given player may act: the match is not over, it is their turn and it is in
+    /// its main phase.
+    ///
+    /// # Returns
+    /// * `Err(GameLogicError::MatchOver)` - If the match has ended.
+    /// * `Err(GameLogicError::NotPlayerTurn)` - If it is not the player's main phase.
+    pub fn ensure_active(&self, player_id: &str) -> Result<(), GameLogicError> {
+        if self.result.is_some() {
+            return Err(GameLogicError::MatchOver);
+        }
+
+        match self.active_player == player_id && self.phase == TurnPhase::Main {
+            true => Ok(()),
+            false => Err(GameLogicError::NotPlayerTurn),
+        }
+    }
+
+    /// Replaces cards from a player's opening hand, then shuffles the returned cards into their
+    /// draw pile. Each player may only do so once, during the mulligan phase.
+    ///
+    /// Replacements are drawn before the returned cards are shuffled in, so they cannot be
+    /// drawn again, unless the draw pile holds fewer cards than were returned.
+    /// Replacements do not fire `on_draw` triggers, like the rest of the opening hand.
+    ///
+    /// # Arguments
+    /// * `player_id` - The player replacing their cards.
+    /// * `instance_ids` - The cards to return, which must all be in the player's hand.
+    ///
+    /// # Returns
+    /// * `Ok(Vec<GameEvent>)` - A `Drew` event for each replacement.
+    /// * `Err(GameLogicError::MulliganUnavailable)` - If the player may not mulligan.
+    /// * `Err(GameLogicError::InvalidMulliganCard)` - If a card is not in the player's hand,
+    ///   or is listed twice. No card is returned then.
+    pub async fn mulligan(
+        &mut self,
+        player_id: &str,
+        instance_ids: &[String],
+    ) -> Result<Vec<GameEvent>, GameLogicError> {
+        if self.result.is_some() {
+            return Err(GameLogicError::MatchOver);
+        }
+        if self.phase != TurnPhase::Mulligan || !self.pending_mulligans.contains(player_id) {
+            return Err(GameLogicError::MulliganUnavailable);
+        }
+
+        let view = self
+            .player_views
+            .read()
+            .await
+            .get(player_id)
+            .cloned()
+            .ok_or(GameLogicError::PlayerNotFound)?;
+
+        let mut returned = Vec::new();
+        {
+            let mut view_guard = view.write().await;
+            let mut listed = HashSet::new();
+            let in_hand = |id: &str| view_guard.current_hand.iter().flatten().any(|c| c.id == id);
+            if let Some(id) = instance_ids
+                .iter()
+                .find(|id| !listed.insert(id.as_str()) || !in_hand(id))
+            {
+                return Err(GameLogicError::InvalidMulliganCard(id.to_string()));
+            }
+
+            for id in instance_ids {
+                if let Some(mut card) = view_guard.take_from_hand(id) {
+                    card.in_deck = true;
+                    returned.push(card);
+                }
+            }
+        }
+
+        let pile_size = self.draw_piles.get(player_id).map_or(0, Vec::len);
+        let mut events = self
+            .draw_replacements(player_id, returned.len().min(pile_size))
+            .await?;
+
+        let pile = self.draw_piles.entry(player_id.to_string()).or_default();
+        let missing = returned.len().saturating_sub(pile_size);
+        view.write().await.deck_size += returned.len();
+        pile.append(&mut returned);
+        pile.shuffle(&mut self.rng);
+
+        events.extend(self.draw_replacements(player_id, missing).await?);
+
+        self.pending_mulligans.remove(player_id);
+        Ok(events)
+    }
+
+    /// Draws the given number of mulligan replacements into a player's hand.
+    ///
+    /// # Returns
+    /// A `Drew` event for each card drawn.
+    async fn draw_replacements(
+        &mut self,
+        player_id: &str,
+        count: usize,
+    ) -> Result<Vec<GameEvent>, GameLogicError> {
+        let mut events = Vec::new();
+        for _ in 0..count {
+            if let DrawOutcome::Drawn(card) = self.draw_card(player_id).await? {
+                events.push(GameEvent::Drew {
+                    owner_id: card.owner_id,
+                });
+            }
+        }
+        Ok(events)
+    }
+
+    /// Ends the match with the given player losing, unless it is already over.
+    pub fn lose(&mut self, loser: &str, reason: MatchEndReason) {
+        if self.result.is_none() {
+            self.result = Some(MatchResult {
+                winner: Some(self.opponent_of(loser).to_string()),
+                reason,
+                turn: self.rounds,
+            });
+        }
+    }
+
+    /// Ends the match if a hero has no health left, unless it is already over. Both heroes
+    /// falling at once is a draw.
+    ///
+    /// # Returns
+    /// The result of the match, if it is over.
+    pub async fn check_heroes(&mut self) -> Option<MatchResult> {
+        if self.result.is_some() {
+            return self.result.clone();
+        }
+
+        let views = Arc::clone(&self.player_views);
+        let views = views.read().await;
+        let mut defeated = Vec::new();
+        for player_id in [&self.red_player, &self.blue_player] {
+            if let Some(view) = views.get(player_id) {
+                if view.read().await.health <= 0 {
+                    defeated.push(player_id.clone());
+                }
+            }
+        }
+
+        match defeated.as_slice() {
+            [] => {}
+            [loser] => self.lose(loser, MatchEndReason::HeroDefeated),
+            _ => {
+                self.result = Some(MatchResult {
+                    winner: None,
+                    reason: MatchEndReason::HeroDefeated,
+                    turn: self.rounds,
+                })
+            }
+        }
+        self.result.clone()
+    }
+
+    /// Builds the view of the match sent to the given player, in which their opponent's view
+    /// is concealed.
+    pub async fn private_view(&self, player_id: &str) -> PrivateGameStateView {
+        let player_views = self.player_views.read().await;
+        let mut red_player = player_views[&self.red_player].read().await.clone();
+        let mut blue_player = player_views[&self.blue_player].read().await.clone();
+        for view in [&mut red_player, &mut blue_player] {
+            if view.id != player_id {
+                view.conceal();
+            }
+        }
+
+        PrivateGameStateView {
+            red_player,
+            blue_player,
+            turn: self.rounds,
+            active_player: self.active_player.clone(),
+            phase: self.phase,
+            fatigue: self.fatigue.clone(),
+        }
+    }
+
+    /// Serializes the view of the match sent to the given player, as the CBOR payload of a
+    /// `GameState` packet.
+    pub async fn wrap_game_state(&self, player_id: &str) -> Result<Vec<u8>, serde_cbor::Error> {
+        serde_cbor::to_vec(&self.private_view(player_id).await)
+    }
+
+    /// Applies the actions returned by a Lua script to the player views.
+    ///
+    /// - `DealDamage` and `Heal` target a hero by its player's id or a creature on either
+    ///   board. Health is clamped between zero and the target's maximum health.
+    /// - `Summon` places a new exhausted copy of a catalog card in a free slot (`0` to `5`) of
+    ///   the owner's board.
+    /// - `GainMana`, `SpendMana` and `LockMana` target a player by their id. Gained mana is
+    ///   capped at `MAX_MANA`, spending more than is available fails, and locked mana is
+    ///   unavailable during the player's next turn.
+    /// - Creatures left without health are moved to their owner's graveyard after each action.
+    ///
+    /// Actions are applied in order to copies of the player views, which only replace them
+    /// once every action has succeeded, so an invalid action leaves the views untouched.
+    ///
+    /// # Arguments
+    /// * `actions` - The actions to apply.
+    /// * `owner_id` - The player whose card produced the actions, receiving summoned creatures.
+    /// * `cards` - The full cards of the match, used to create summoned creatures.
+    ///
+    /// # Returns
+    /// * `Ok(Vec<GameEvent>)` - The resulting events, in order.
+    /// * `Err(GameLogicError)` - If an action has an invalid target or position, or spends
+    ///   more mana than available.
+    pub async fn apply_actions(
+        &self,
+        actions: Vec<GameAction>,
+        owner_id: &str,
+        cards: &HashMap<String, Card>,
+    ) -> Result<Vec<GameEvent>, GameLogicError> {
+        // Views are locked in the same order every time, and for the whole batch so that no
+        // concurrent change is overwritten by the copies.
+        let mut views: Vec<_> = self.player_views.read().await.clone().into_iter().collect();
+        views.sort_by(|(a, _), (b, _)| a.cmp(b));
+        let mut guards = Vec::new();
+        for (_, view) in &views {
+            guards.push(view.write().await);
+        }
+        let mut staged: Vec<PlayerView> = guards.iter().map(|guard| (**guard).clone()).collect();
+
+        let mut events = Vec::new();
+        for action in actions {
+            match action {
+                GameAction::DealDamage { target, amount } => {
+                    let health = Self::change_health(&mut staged, &target, |health, _| {
+                        (health - to_points(amount)).max(0)
+                    })?;
+                    events.push(GameEvent::Damaged {
+                        target,
+                        amount,
+                        health,
+                    });
+                }
+                GameAction::Heal { target, amount } => {
+                    let health =
+                        Self::change_health(&mut staged, &target, |health, max_health| {
+                            (health + to_points(amount)).min(max_health.max(health))
+                        })?;
+                    events.push(GameEvent::Healed {
+                        target,
+                        amount,
+                        health,
+                    });
+                }
+                GameAction::Summon { id, position } => {
+                    let card = cards
+                        .get(&id)
+                        .ok_or(GameLogicError::UnableToGetCardDetails)?;
+                    let owner = staged
+                        .iter_mut()
+                        .find(|view| view.id == owner_id)
+                        .ok_or(GameLogicError::PlayerNotFound)?;
+
+                    let slot = owner.free_creature_slot(&position)?;
+                    let creature = self.create_card(card, owner_id);
+                    let creature = owner.place_creature(creature, &position)?;
+                    events.push(GameEvent::Summoned {
+                        instance_id: creature.id,
+                        card_id: id,
+                        owner_id: owner_id.to_string(),
+                        position: slot,
+                    });
+                }
+                GameAction::GainMana { target, amount } => {
+                    let event = Self::change_mana(&mut staged, &target, |view| {
+                        view.mana = (view.mana + to_points(amount)).min(MAX_MANA);
+                        Ok(())
+                    })?;
+                    events.push(event);
+                }
+                GameAction::SpendMana { target, amount } => {
+                    let event = Self::change_mana(&mut staged, &target, |view| {
+                        view.spend_mana(to_points(amount))
+                    })?;
+                    events.push(event);
+                }
+                GameAction::LockMana { target, amount } => {
+                    let event = Self::change_mana(&mut staged, &target, |view| {
+                        view.overload = (view.overload + to_points(amount)).min(MAX_MANA);
+                        Ok(())
+                    })?;
+                    events.push(event);
+                }
+            }
+
+            for view in staged.iter_mut() {
+                for card in view.remove_dead_creatures() {
+                    events.push(GameEvent::Died {
+                        instance_id: card.id,
+                        owner_id: card.owner_id,
+                    });
+                }
+            }
+        }
+
+        for (guard, view) in guards.iter_mut().zip(staged) {
+            **guard = view;
+        }
+        Ok(events)
+    }
+
+    /// Moves every creature left without health to its owner's graveyard.
+    ///
+    /// # Returns
+    /// A `Died` event for each creature removed from a board.
+    pub async fn resolve_deaths(&self) -> Vec<GameEvent> {
+        let views: Vec<_> = self.player_views.read().await.values().cloned().collect();
+        let mut events = Vec::new();
+        for view in views {
+            for card in view.write().await.remove_dead_creatures() {
+                events.push(GameEvent::Died {
+                    instance_id: card.id,
+                    owner_id: card.owner_id,
+                });
+            }
+        }
+
+        events
+    }
+
+    /// Ensures one of the actor's creatures may attack the given target.
+    ///
+    /// # Arguments
+    /// * `actor_id` - The player attacking.
+    /// * `attacker_id` - The instance id of a creature on the actor's board.
+    /// * `target_id` - The instance id of a creature on the enemy board, or the enemy player's
+    ///   id to hit their hero.
+    ///
+    /// # Returns
+    /// * `Ok((CardView, Option<CardView>))` - The attacker, and the targeted creature or `None`
+    ///   for the enemy hero.
+    /// * `Err(GameLogicError::AttackerNotOnBoard)` - If the attacker is not on the actor's board.
+    /// * `Err(GameLogicError::AttackerExhausted)` - If the attacker has already acted.
+    /// * `Err(GameLogicError::InvalidAttackTarget)` - If the target is not an enemy.
+    pub async fn attack_target(
+        &self,
+        actor_id: &str,
+        attacker_id: &str,
+        target_id: &str,
+    ) -> Result<(CardView, Option<CardView>), GameLogicError> {
+        let (actor_view, opponent_view) = self.player_and_opponent(actor_id).await?;
+        let actor = actor_view.read().await;
+        let opponent = opponent_view.read().await;
+
+        let attacker = actor
+            .board
+            .creature(attacker_id)
+            .ok_or(GameLogicError::AttackerNotOnBoard)?;
+        if attacker.is_exhausted {
+            return Err(GameLogicError::AttackerExhausted);
+        }
+
+        // The enemy player's id designates their hero.
+        let target = match target_id == opponent.id {
+            true => None,
+            false => Some(
+                opponent
+                    .board
+                    .creature(target_id)
+                    .ok_or(GameLogicError::InvalidAttackTarget)?
+                    .clone(),
+            ),
+        };
+        Ok((attacker.clone(), target))
+    }
+
+    /// Exhausts an attacker and exchanges damage with its target: creatures deal their attack
+    /// to each other, while heroes do not strike back. Creatures left without health stay on
+    /// the board until `resolve_deaths`.
+    ///
+    /// # Arguments
+    /// * `actor_id` - The player attacking.
+    /// * `attacker_id` - The instance id of a creature on the actor's board.
+    /// * `target_id` - The instance id of a creature on the enemy board, or `None` for the
+    ///   enemy hero.
+    ///
+    /// # Returns
+    /// * `Ok((Vec<GameEvent>, CardView, Option<CardView>))` - The `Damaged` events, followed by
+    ///   the attacker and the targeted creature as they are after the exchange.
+    /// * `Err(GameLogicError)` - If either creature is no longer on its board, in which case
+    ///   nothing is changed.
+    pub async fn exchange_damage(
+        &self,
+        actor_id: &str,
+        attacker_id: &str,
+        target_id: Option<&str>,
+    ) -> Result<(Vec<GameEvent>, CardView, Option<CardView>), GameLogicError> {
+        let (actor_view, opponent_view) = self.player_and_opponent(actor_id).await?;
+        let mut actor = actor_view.write().await;
+        let mut opponent = opponent_view.write().await;
+
+        let attacker = actor
+            .board
+            .creature_mut(attacker_id)
+            .ok_or(GameLogicError::AttackerNotOnBoard)?;
+
+        let mut events = Vec::new();
+        let target = match target_id {
+            None => {
+                opponent.health = (opponent.health - attacker.attack).max(0);
+                events.push(GameEvent::Damaged {
+                    target: opponent.id.clone(),
+                    amount: attacker.attack.max(0) as u32,
+                    health: opponent.health,
+                });
+                None
+            }
+            Some(target_id) => {
+                let defender = opponent
+                    .board
+                    .creature_mut(target_id)
+                    .ok_or(GameLogicError::InvalidAttackTarget)?;
+                defender.health = (defender.health - attacker.attack).max(0);
+                attacker.health = (attacker.health - defender.attack).max(0);
+                events.push(GameEvent::Damaged {
+                    target: defender.id.clone(),
+                    amount: attacker.attack.max(0) as u32,
+                    health: defender.health,
+                });
+                events.push(GameEvent::Damaged {
+                    target: attacker.id.clone(),
+                    amount: defender.attack.max(0) as u32,
+                    health: attacker.health,
+                });
+                Some(defender.clone())
+            }
+        };
+
+        attacker.is_exhausted = true;
+        Ok((events, attacker.clone(), target))
+    }
+
+    /// Returns the views of the given player and of their opponent.
+    pub async fn player_and_opponent(
+        &self,
+        player_id: &str,
+    ) -> Result<(Arc<RwLock<PlayerView>>, Arc<RwLock<PlayerView>>), GameLogicError> {
+        let player_views = self.player_views.read().await;
+        let player = player_views
+            .get(player_id)
+            .ok_or(GameLogicError::PlayerNotFound)?;
+        let opponent = player_views
+            .get(self.opponent_of(player_id))
+            .ok_or(GameLogicError::PlayerNotFound)?;
+
+        Ok((Arc::clone(player), Arc::clone(opponent)))
+    }
+
+    /// Updates the health of a hero or creature on any board.
+    ///
+    /// # Arguments
+    /// * `views` - The views of every player.
+    /// * `target` - A player's id for their hero, or the id of a creature on a board.
+    /// * `update` - Computes the new health from the current and maximum health.
+    ///
+    /// # Returns
+    /// The target's new health, or `InvalidActionTarget` if no hero or creature matches.
+    fn change_health(
+        views: &mut [PlayerView],
+        target: &str,
+        update: impl Fn(i32, i32) -> i32,
+    ) -> Result<i32, GameLogicError> {
+        for view in views {
+            if let Some((health, max_health)) = view.health_mut(target) {
+                *health = update(*health, max_health);
+                return Ok(*health);
+            }
         }
+
+        Err(GameLogicError::InvalidActionTarget(target.to_string()))
     }
 
-    /// Wraps the game state into a byte array for transmission or storage.
-    pub fn wrap_game_state(&self) -> Box<[u8]> {
-        Box::new(b"Pretend this is the wrapped game state".to_owned())
+    /// Updates the mana of a player.
+    ///
+    /// # Arguments
+    /// * `views` - The views of every player.
+    /// * `target` - The id of the player.
+    /// * `update` - Changes the player's mana, failing if the change is not allowed.
+    ///
+    /// # Returns
+    /// The resulting `ManaChanged` event, or `InvalidActionTarget` if no player matches.
+    fn change_mana(
+        views: &mut [PlayerView],
+        target: &str,
+        update: impl FnOnce(&mut PlayerView) -> Result<(), GameLogicError>,
+    ) -> Result<GameEvent, GameLogicError> {
+        let view = views
+            .iter_mut()
+            .find(|view| view.id == target)
+            .ok_or_else(|| GameLogicError::InvalidActionTarget(target.to_string()))?;
+
+        update(view)?;
+        Ok(view.mana_event())
     }
+}
 
-    pub async fn apply_actions(&self, actions: Vec<GameAction>) {}
+/// Converts an action amount to health or mana points, saturating at `i32::MAX`.
+fn to_points(amount: u32) -> i32 {
+    i32::try_from(amount).unwrap_or(i32::MAX)
 }
 
-#[derive(Serialize, Clone)]
+#[derive(Serialize, Clone, JsonSchema)]
 pub struct PrivateGameStateView {
     pub turn: u32,
+    pub active_player: String,
+    pub phase: TurnPhase,
     pub red_player: PlayerView,
     pub blue_player: PlayerView,
+    pub fatigue: HashMap<String, u32>,
 }
 
-#[derive(Serialize, Clone)]
+#[derive(Serialize, Clone, JsonSchema)]
 pub struct PublicGameStateView {
     pub turn: u32,
+    pub active_player: String,
+    pub phase: TurnPhase,
     pub red_player: PublicPlayerView,
     pub blue_player: PublicPlayerView,
-}
\ No newline at end of file
+    pub fatigue: HashMap<String, u32>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn card(id: &str, health: i32) -> Card {
+        Card {
+            id: id.to_string(),
+            name: id.to_string(),
+            description: String::new(),
+            play_cost: 1,
+            attack: 1,
+            health,
+            rarity: 0,
+            on_play: Vec::new(),
+            on_draw: Vec::new(),
+            on_attack: Vec::new(),
+            on_hit: Vec::new(),
+            on_turn_start: Vec::new(),
+            on_turn_end: Vec::new(),
+            on_death: Vec::new(),
+            on_ally_death: Vec::new(),
+            on_enemy_death: Vec::new(),
+        }
+    }
+
+    fn game_state() -> GameState {
+        let mut views = HashMap::new();
+        for id in ["red", "blue"] {
+            let view = PlayerView::from_player(id, 0);
+            views.insert(id.to_string(), Arc::new(RwLock::new(view)));
+        }
+        GameState::new_game(views, "red".to_string(), "blue".to_string(), 7)
+    }
+
+    #[tokio::test]
+    async fn test_apply_actions() {
+        let state = game_state();
+        let cards = HashMap::from([("wolf".to_string(), card("wolf", 2))]);
+        let actions = vec![
+            GameAction::Summon {
+                id: "wolf".to_string(),
+                position: "0".to_string(),
+            },
+            GameAction::DealDamage {
+                target: "blue".to_string(),
+                amount: 5,
+            },
+            GameAction::Heal {
+                target: "blue".to_string(),
+                amount: 10,
+            },
+            // Summoned creatures are targeted by their instance id
+            GameAction::DealDamage {
+                target: "card-1".to_string(),
+                amount: 3,
+            },
+        ];
+
+        let events = state.apply_actions(actions, "red", &cards).await.unwrap();
+        assert_eq!(events.len(), 5);
+        assert!(matches!(events[2], GameEvent::Healed { health: 30, .. }));
+        assert!(matches!(events[3], GameEvent::Damaged { health: 0, .. }));
+        assert!(matches!(events[4], GameEvent::Died { .. }));
+
+        let views = state.player_views.read().await;
+        let red = views["red"].read().await;
+        assert!(red.board.creature("card-1").is_none());
+        assert_eq!(red.graveyard_size, 1);
+    }
+
+    #[tokio::test]
+    async fn test_apply_actions_invalid_target() {
+        let state = game_state();
+        let actions = vec![
+            GameAction::DealDamage {
+                target: "blue".to_string(),
+                amount: 5,
+            },
+            GameAction::DealDamage {
+                target: "nobody".to_string(),
+                amount: 1,
+            },
+        ];
+
+        let result = state.apply_actions(actions, "red", &HashMap::new()).await;
+        assert!(matches!(result, Err(GameLogicError::InvalidActionTarget(_))));
+
+        // Actions preceding the invalid one are not applied either
+        let views = state.player_views.read().await;
+        assert_eq!(views["blue"].read().await.health, 30);
+    }
+
+    #[tokio::test]
+    async fn test_apply_mana_actions() {
+        let state = game_state();
+        state.player_views.read().await["red"]
+            .write()
+            .await
+            .refill_mana();
+        let actions = vec![
+            GameAction::GainMana {
+                target: "red".to_string(),
+                amount: 2,
+            },
+            GameAction::SpendMana {
+                target: "red".to_string(),
+                amount: 3,
+            },
+            GameAction::LockMana {
+                target: "red".to_string(),
+                amount: 1,
+            },
+        ];
+
+        let events = state
+            .apply_actions(actions, "red", &HashMap::new())
+            .await
+            .unwrap();
+        assert!(matches!(
+            events[2],
+            GameEvent::ManaChanged {
+                mana: 0,
+                overload: 1,
+                ..
+            }
+        ));
+
+        let overspend = vec![GameAction::SpendMana {
+            target: "red".to_string(),
+            amount: 1,
+        }];
+        let result = state.apply_actions(overspend, "red", &HashMap::new()).await;
+        assert!(matches!(result, Err(GameLogicError::NotEnoughMana(1, 0))));
+    }
+
+    #[tokio::test]
+    async fn test_draw_card() {
+        let mut state = game_state();
+        let pile = |state: &GameState| -> Vec<String> {
+            state.draw_piles["red"]
+                .iter()
+                .map(|card| card.id.clone())
+                .collect()
+        };
+        let cards: Vec<CardView> = (0..12)
+            .map(|index| state.create_card(&card(&index.to_string(), 1), "red"))
+            .collect();
+
+        // The same seed always deals the same order
+        state.build_draw_pile("red", cards.clone()).await;
+        let mut replay = game_state();
+        replay.build_draw_pile("red", cards).await;
+        assert_eq!(pile(&state), pile(&replay));
+
+        for _ in 0..10 {
+            assert!(matches!(
+                state.draw_card("red").await,
+                Ok(DrawOutcome::Drawn(_))
+            ));
+        }
+        assert!(matches!(
+            state.draw_card("red").await,
+            Ok(DrawOutcome::Burned(_))
+        ));
+
+        {
+            let views = state.player_views.read().await;
+            let red = views["red"].read().await;
+            assert_eq!(
+                (red.hand_size, red.deck_size, red.graveyard_size),
+                (10, 1, 1)
+            );
+        }
+
+        // Once the deck is empty, each draw deals one more fatigue damage than the last
+        state.draw_card("red").await.unwrap();
+        assert!(matches!(
+            state.draw_card("red").await,
+            Ok(DrawOutcome::Fatigue {
+                damage: 1,
+                health: 29
+            })
+        ));
+        assert!(matches!(
+            state.draw_card("red").await,
+            Ok(DrawOutcome::Fatigue {
+                damage: 2,
+                health: 27
+            })
+        ));
+        assert_eq!(state.fatigue["red"], 2);
+        assert!(!state.fatigue.contains_key("blue"));
+    }
+
+    #[tokio::test]
+    async fn test_mulligan() {
+        let mut state = game_state();
+        let cards: Vec<CardView> = (0..6)
+            .map(|index| state.create_card(&card(&index.to_string(), 1), "red"))
+            .collect();
+        state.build_draw_pile("red", cards).await;
+        for _ in 0..OPENING_HAND_SIZE {
+            state.draw_card("red").await.unwrap();
+        }
+
+        let hand: Vec<String> = {
+            let views = state.player_views.read().await;
+            let red = views["red"].read().await;
+            red.current_hand
+                .iter()
+                .flatten()
+                .map(|c| c.id.clone())
+                .collect()
+        };
+        let returned = vec![hand[0].clone(), hand[1].clone()];
+        assert!(matches!(
+            state.mulligan("red", &returned).await,
+            Err(GameLogicError::MulliganUnavailable)
+        ));
+
+        state.phase = TurnPhase::Mulligan;
+        state.pending_mulligans.insert("red".to_string());
+        let duplicated = vec![hand[0].clone(), hand[0].clone()];
+        assert!(matches!(
+            state.mulligan("red", &duplicated).await,
+            Err(GameLogicError::InvalidMulliganCard(_))
+        ));
+
+        let events = state.mulligan("red", &returned).await.unwrap();
+        assert_eq!(events.len(), 2);
+        {
+            let views = state.player_views.read().await;
+            let red = views["red"].read().await;
+            assert_eq!((red.hand_size, red.deck_size), (OPENING_HAND_SIZE, 3));
+            // Returned cards are shuffled back only once the replacements are drawn
+            let hand = red.current_hand.iter().flatten();
+            assert!(hand.map(|c| &c.id).all(|id| !returned.contains(id)));
+            assert!(state.draw_piles["red"].iter().all(|c| c.in_deck));
+        }
+
+        // A player may only mulligan once
+        assert!(state.mulligan("red", &[]).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_private_view() {
+        let mut state = game_state();
+        for owner_id in ["red", "blue"] {
+            let cards = vec![state.create_card(&card(owner_id, 1), owner_id)];
+            state.build_draw_pile(owner_id, cards).await;
+            state.draw_card(owner_id).await.unwrap();
+        }
+
+        // Each player sees their own hand, but only the size of their opponent's
+        let view = state.private_view("red").await;
+        assert_eq!(view.red_player.current_hand.iter().flatten().count(), 1);
+        assert_eq!(view.blue_player.current_hand.iter().flatten().count(), 0);
+        assert_eq!(view.blue_player.hand_size, 1);
+
+        let view = state.private_view("blue").await;
+        assert_eq!(view.red_player.current_hand.iter().flatten().count(), 0);
+        assert_eq!(view.blue_player.current_hand.iter().flatten().count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_check_heroes() {
+        let mut state = game_state();
+        assert!(state.check_heroes().await.is_none());
+
+        state.player_views.read().await["blue"].write().await.health = 0;
+        let result = state.check_heroes().await.unwrap();
+        assert_eq!(result.winner.as_deref(), Some("red"));
+        assert_eq!(result.reason, MatchEndReason::HeroDefeated);
+        assert!(matches!(
+            state.ensure_active("red"),
+            Err(GameLogicError::MatchOver)
+        ));
+
+        // Both heroes falling together is a draw
+        let mut state = game_state();
+        for view in state.player_views.read().await.values() {
+            view.write().await.health = 0;
+        }
+        assert_eq!(state.check_heroes().await.unwrap().winner, None);
+
+        // The first result is kept
+        state.lose("red", MatchEndReason::Conceded);
+        assert_eq!(state.result.unwrap().reason, MatchEndReason::HeroDefeated);
+    }
+
+    /// Places a ready creature with the given attack and health on a player's board.
+    async fn place(state: &GameState, owner_id: &str, attack: i32, health: i32) -> String {
+        let mut creature = state.create_card(&card("creature", health), owner_id);
+        creature.attack = attack;
+        let view = Arc::clone(&state.player_views.read().await[owner_id]);
+        let mut view_guard = view.write().await;
+        let slot = (0..6).find(|slot| view_guard.free_creature_slot(&slot.to_string()).is_ok());
+        let creature = view_guard
+            .place_creature(creature, &slot.unwrap().to_string())
+            .unwrap();
+        if let Some(creature) = view_guard.board.creature_mut(&creature.id) {
+            creature.is_exhausted = false;
+        }
+        creature.id
+    }
+
+    #[tokio::test]
+    async fn test_attack_target() {
+        let state = game_state();
+        let attacker = place(&state, "red", 2, 3).await;
+        let defender = place(&state, "blue", 1, 1).await;
+
+        let (_, target) = state.attack_target("red", &attacker, "blue").await.unwrap();
+        assert!(target.is_none());
+        let (_, target) = state
+            .attack_target("red", &attacker, &defender)
+            .await
+            .unwrap();
+        assert_eq!(target.unwrap().id, defender);
+
+        // Only enemy creatures and the enemy hero can be attacked, by ready creatures
+        assert!(matches!(
+            state.attack_target("red", &attacker, "nobody").await,
+            Err(GameLogicError::InvalidAttackTarget)
+        ));
+        assert!(matches!(
+            state.attack_target("red", &attacker, "red").await,
+            Err(GameLogicError::InvalidAttackTarget)
+        ));
+        assert!(matches!(
+            state.attack_target("blue", &attacker, "red").await,
+            Err(GameLogicError::AttackerNotOnBoard)
+        ));
+
+        state.player_views.read().await["red"]
+            .write()
+            .await
+            .board
+            .creature_mut(&attacker)
+            .unwrap()
+            .is_exhausted = true;
+        assert!(matches!(
+            state.attack_target("red", &attacker, "blue").await,
+            Err(GameLogicError::AttackerExhausted)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_exchange_damage() {
+        let state = game_state();
+        let attacker = place(&state, "red", 2, 3).await;
+        let defender = place(&state, "blue", 1, 2).await;
+
+        // Heroes do not strike back
+        let (events, attacker_view, _) =
+            state.exchange_damage("red", &attacker, None).await.unwrap();
+        assert_eq!(events.len(), 1);
+        assert!(matches!(events[0], GameEvent::Damaged { health: 28, .. }));
+        assert!(attacker_view.is_exhausted);
+
+        // Creatures deal their attack to each other
+        let (events, attacker_view, target) = state
+            .exchange_damage("red", &attacker, Some(&defender))
+            .await
+            .unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!((attacker_view.health, target.unwrap().health), (2, 0));
+
+        // Only the creature left without health goes to the graveyard
+        let deaths = state.resolve_deaths().await;
+        assert_eq!(deaths.len(), 1);
+        let views = state.player_views.read().await;
+        let blue = views["blue"].read().await;
+        assert!(blue.board.creature(&defender).is_none());
+        assert_eq!(blue.graveyard.creatures[0].id, defender);
+        let red = views["red"].read().await;
+        assert!(red.board.creature(&attacker).is_some());
+    }
+
+    #[test]
+    fn test_ensure_active() {
+        let mut state = game_state();
+        assert!(state.ensure_active("red").is_err());
+
+        state.rounds = 1;
+        state.active_player = state.first_player().to_string();
+        state.phase = TurnPhase::Main;
+        assert!(state.ensure_active("red").is_ok());
+        assert!(state.ensure_active("blue").is_err());
+        assert_eq!(state.opponent_of("red"), "blue");
+
+        // Actions are only allowed during the main phase
+        state.phase = TurnPhase::End;
+        assert!(state.ensure_active("red").is_err());
+    }
+}
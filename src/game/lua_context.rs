@@ -10,7 +10,7 @@ pub struct LuaContext {
     pub event: String,
     pub action_name: String,
 
-    pub actor_id: String,
+    pub actor_id: String, // Instance id of the acting card, its catalog id is in `actor_view`.
     pub actor_view: CardView,
     pub target_id: Option<String>,
     pub target_view: Option<CardView>,
@@ -39,14 +39,13 @@ impl LuaContext {
         let game_state_guard = game_state.read().await;
         let player_views_guard = game_state_guard.player_views.read().await;
 
-        let keys: Vec<_> = player_views_guard.keys().collect();
-        let red_player = player_views_guard[keys[0]]
+        let red_player = player_views_guard[&game_state_guard.red_player]
             .clone()
             .read()
             .await
             .clone();
         
-        let blue_player = player_views_guard[keys[1]]
+        let blue_player = player_views_guard[&game_state_guard.blue_player]
             .clone()
             .read()
             .await
@@ -56,6 +55,9 @@ impl LuaContext {
             red_player,
             blue_player,
             turn: game_state_guard.rounds,
+            active_player: game_state_guard.active_player.clone(),
+            phase: game_state_guard.phase,
+            fatigue: game_state_guard.fatigue.clone(),
         };
 
         LuaContext {
@@ -2,6 +2,7 @@ use config::{Config, File};
 use models::settings::Settings;
 use std::{io::Error, sync::Arc};
 use std::sync::LazyLock;
+use std::time::Duration;
 use tcp::server::ServerInstance;
 use tokio::sync::OnceCell;
 use crate::tcp::server::UninitializedServer;
@@ -14,8 +15,17 @@ mod utils;
 static SETTINGS: OnceCell<Settings> = OnceCell::const_new();
 static SERVER_INSTANCE: OnceCell<ServerInstance> = OnceCell::const_new();
 
+/// Longest wait for the final packets to reach the clients once the match has ended.
+const MATCH_END_DRAIN_TIMEOUT: Duration = Duration::from_secs(5);
+
 #[tokio::main]
 async fn main() -> Result<(), Error> {
+    // `--export-schema [path]` writes the protocol schema for client code generation and exits.
+    let args: Vec<String> = std::env::args().collect();
+    if args.get(1).map(String::as_str) == Some("--export-schema") {
+        return tcp::schema::export(args.get(2).map(String::as_str));
+    }
+
     SETTINGS
         .set(
             Config::builder()
@@ -33,7 +43,22 @@ async fn main() -> Result<(), Error> {
         let server_arc = Arc::new(uninitialized);
         if let Ok(initialized_server) = Arc::clone(&server_arc).await_for_initialization().await {
             let initialized_clone = Arc::new(initialized_server);
-            initialized_clone.listen().await;
+            Arc::clone(&initialized_clone).listen().await;
+
+            // The listen loop only returns once the match has ended. Give the clients a moment to
+            // receive the result before exiting with the recorded code.
+            initialized_clone
+                .drain_clients(MATCH_END_DRAIN_TIMEOUT)
+                .await;
+            let exit_code = initialized_clone
+                .exit_status
+                .read()
+                .await
+                .as_ref()
+                .map(|s| s.code);
+            if let Some(code) = exit_code {
+                std::process::exit(code);
+            }
         }
     }
 
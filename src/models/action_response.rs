@@ -0,0 +1,74 @@
+use crate::models::error_response::ErrorEnvelope;
+use crate::models::game_event::GameEvent;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Payload answering every client action, sent with the action's own header type.
+///
+/// Echoes the action's `request_id` so the client can confirm or roll back the exact
+/// optimistic action it applied. `error` is only set when the action was rejected, while
+/// `events` lists what the action caused, in order, so the client can animate it.
+#[derive(Serialize, Deserialize, Debug, Clone, JsonSchema)]
+pub struct ActionResponse {
+    pub request_id: u32,
+    pub success: bool,
+    pub error: Option<ErrorEnvelope>,
+    #[serde(default)]
+    pub events: Vec<GameEvent>,
+}
+
+/// The request id of an action payload, read on its own so that it can still be echoed
+/// when the rest of the payload is invalid.
+#[derive(Deserialize, Default)]
+struct RequestIdentifier {
+    #[serde(default)]
+    request_id: u32,
+}
+
+impl ActionResponse {
+    /// Creates the response of an action that was applied.
+    pub fn accepted(request_id: u32, events: Vec<GameEvent>) -> Self {
+        Self {
+            request_id,
+            success: true,
+            error: None,
+            events,
+        }
+    }
+
+    /// Creates the response of an action that was rejected.
+    pub fn rejected(request_id: u32, error: ErrorEnvelope) -> Self {
+        Self {
+            request_id,
+            success: false,
+            error: Some(error),
+            events: Vec::new(),
+        }
+    }
+
+    /// Reads the request id of an action payload, defaulting to `0` when it is missing.
+    ///
+    /// # Arguments
+    /// - `payload`: The CBOR payload of the action packet.
+    pub fn request_id_of(payload: &[u8]) -> u32 {
+        serde_cbor::from_slice::<RequestIdentifier>(payload)
+            .unwrap_or_default()
+            .request_id
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_request_id_of_invalid_action() {
+        // The card id is missing, yet the request id can still be echoed back
+        let mut payload = BTreeMap::new();
+        payload.insert("request_id", 42);
+        let payload = serde_cbor::to_vec(&payload).unwrap();
+        assert_eq!(ActionResponse::request_id_of(&payload), 42);
+        assert_eq!(ActionResponse::request_id_of(b"not cbor"), 0);
+    }
+}
@@ -1,22 +1,94 @@
+use crate::utils::checksum::ChecksumAlgorithm;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-#[derive(Serialize, Deserialize, Debug, Default)]
+/// Features a client asks for when connecting, in order of preference.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, JsonSchema)]
+pub struct RequestedCapabilities {
+    #[serde(default)]
+    pub compression: bool,
+    #[serde(default)]
+    pub checksum_algorithms: Vec<ChecksumAlgorithm>,
+    #[serde(default)]
+    pub max_payload_length: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, JsonSchema)]
 pub struct ConnectionRequest {
     pub player_id: String,
     pub auth_token: String,
     pub current_deck_id: String,
+    #[serde(default)]
+    pub protocol_version: Option<u16>,
+    #[serde(default)]
+    pub capabilities: RequestedCapabilities,
 }
 
-#[derive(Serialize, Deserialize, Debug, Default)]
+#[derive(Serialize, Deserialize, Debug, Default, JsonSchema)]
 pub struct ReconnectionRequest {
     pub player_id: String,
     pub auth_token: String,
+    #[serde(default)]
+    pub protocol_version: Option<u16>,
+    #[serde(default)]
+    pub capabilities: RequestedCapabilities,
+    #[serde(default)]
+    pub last_sequence: Option<u32>,
 }
 
-#[derive(Serialize, Deserialize, Debug, Default)]
+#[derive(Serialize, Deserialize, Debug, Default, JsonSchema)]
+pub struct AcknowledgeRequest {
+    pub sequence: u32,
+}
+
+/// Every client action carries a `request_id` chosen by the client, echoed back in its `ActionResponse`.
+///
+/// `instance_id` names the exact copy to play from the actor's hand.
+#[derive(Serialize, Deserialize, Debug, Default, JsonSchema)]
 pub struct PlayCardRequest {
+    #[serde(default)]
+    pub request_id: u32,
     pub actor_id: String,
-    pub card_id: String,
+    pub instance_id: String,
     pub target_id: Option<String>,
     pub target_position: Option<String>,
-}
\ No newline at end of file
+}
+
+/// Attack with one of the actor's creatures. `target_id` is either an enemy creature or the
+/// enemy player's id, to attack their hero. Creatures are named by their instance id.
+#[derive(Serialize, Deserialize, Debug, Default, JsonSchema)]
+pub struct AttackRequest {
+    #[serde(default)]
+    pub request_id: u32,
+    pub actor_id: String,
+    pub attacker_id: String,
+    pub target_id: String,
+}
+
+/// Ends the actor's turn, passing it to their opponent.
+#[derive(Serialize, Deserialize, Debug, Default, JsonSchema)]
+pub struct EndTurnRequest {
+    #[serde(default)]
+    pub request_id: u32,
+    pub actor_id: String,
+}
+
+/// Returns cards from the actor's opening hand during the mulligan phase, named by instance id.
+/// An empty list keeps the whole hand.
+#[derive(Serialize, Deserialize, Debug, Default, JsonSchema)]
+pub struct MulliganRequest {
+    #[serde(default)]
+    pub request_id: u32,
+    pub actor_id: String,
+    #[serde(default)]
+    pub instance_ids: Vec<String>,
+}
+
+/// Gives up the match, which the actor's opponent wins. Allowed at any time, even outside of
+/// the actor's turn.
+#[derive(Serialize, Deserialize, Debug, Default, JsonSchema)]
+pub struct ConcedeRequest {
+    #[serde(default)]
+    pub request_id: u32,
+    pub actor_id: String,
+}
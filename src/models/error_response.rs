@@ -0,0 +1,172 @@
+use crate::tcp::handshake::SupportedVersions;
+use crate::utils::errors::{
+    GameLogicError, PlayerConnectionError, ProtocolError, ServerInstanceError,
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_cbor::Value;
+
+/// Broad family of an error, letting clients handle unknown codes sensibly.
+///
+/// Codes are grouped by category: `1xxx` protocol, `2xxx` connection, `3xxx` game and `4xxx` server.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, JsonSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ErrorCategory {
+    Protocol,
+    Connection,
+    Game,
+    Server,
+}
+
+/// CBOR payload of every error the server sends to a client.
+#[derive(Serialize, Deserialize, Debug, Clone, JsonSchema)]
+pub struct ErrorEnvelope {
+    pub code: u16,
+    pub category: ErrorCategory,
+    pub message: String,
+    #[schemars(with = "Option<serde_json::Value>")]
+    pub details: Option<Value>,
+}
+
+/// Maps an error to the fields of its `ErrorEnvelope`.
+pub trait ErrorCode: std::error::Error {
+    /// Stable numeric code of the error.
+    fn code(&self) -> u16;
+
+    /// Category of the error.
+    fn category(&self) -> ErrorCategory;
+
+    /// Machine-readable context for the error, if any.
+    fn details(&self) -> Option<Value> {
+        None
+    }
+}
+
+impl<E: ErrorCode> From<&E> for ErrorEnvelope {
+    fn from(error: &E) -> Self {
+        Self {
+            code: error.code(),
+            category: error.category(),
+            message: error.to_string(),
+            details: error.details(),
+        }
+    }
+}
+
+impl ErrorCode for ProtocolError {
+    fn code(&self) -> u16 {
+        match self {
+            ProtocolError::InvalidHeaderError(_) => 1001,
+            ProtocolError::InvalidPacketError(_) => 1002,
+            ProtocolError::InvalidPacketPayload(_) => 1003,
+            ProtocolError::InvalidChecksum => 1004,
+            ProtocolError::CompressionNotNegotiated => 1005,
+        }
+    }
+
+    fn category(&self) -> ErrorCategory {
+        ErrorCategory::Protocol
+    }
+}
+
+impl ErrorCode for PlayerConnectionError {
+    fn code(&self) -> u16 {
+        match self {
+            PlayerConnectionError::InvalidResponseBody(_) => 2001,
+            PlayerConnectionError::BannedPlayer(_) => 2002,
+            PlayerConnectionError::InvalidPlayerPayload(_) => 2003,
+            PlayerConnectionError::PlayerDiscrepancy => 2004,
+            PlayerConnectionError::PlayerNotConnected => 2005,
+            PlayerConnectionError::UnauthorizedPlayerError => 2006,
+            PlayerConnectionError::UnsupportedProtocolVersion(_) => 2007,
+            PlayerConnectionError::UnexpectedPlayerError(_) => 2008,
+            PlayerConnectionError::DeckNotFound => 2009,
+            PlayerConnectionError::InvalidDeckFormat => 2010,
+            PlayerConnectionError::UnexpectedDeckError(_) => 2011,
+            PlayerConnectionError::UnauthorizedDeckError => 2012,
+            PlayerConnectionError::InternalError(_) => 2013,
+        }
+    }
+
+    fn category(&self) -> ErrorCategory {
+        ErrorCategory::Connection
+    }
+
+    /// Unsupported protocol versions carry the `SupportedVersions` the client may retry with.
+    fn details(&self) -> Option<Value> {
+        match self {
+            PlayerConnectionError::UnsupportedProtocolVersion(_) => {
+                serde_cbor::value::to_value(SupportedVersions::default()).ok()
+            }
+            _ => None,
+        }
+    }
+}
+
+impl ErrorCode for GameLogicError {
+    fn code(&self) -> u16 {
+        match self {
+            GameLogicError::CardPlayedIsNotInHand => 3001,
+            GameLogicError::UnableToGetCardDetails => 3002,
+            GameLogicError::PlayerIdDoesNotMatch => 3003,
+            GameLogicError::PlayerNotFound => 3004,
+            GameLogicError::FunctionNotFound(_, _) => 3005,
+            GameLogicError::FunctionNotCallable(_) => 3006,
+            GameLogicError::InvalidGameActions => 3007,
+            GameLogicError::NotPlayerTurn => 3008,
+            GameLogicError::AttackerNotOnBoard => 3009,
+            GameLogicError::AttackerExhausted => 3010,
+            GameLogicError::InvalidAttackTarget => 3011,
+            GameLogicError::InvalidActionTarget(_) => 3012,
+            GameLogicError::InvalidSummonPosition(_) => 3013,
+            GameLogicError::NotEnoughMana(..) => 3014,
+            GameLogicError::MulliganUnavailable => 3015,
+            GameLogicError::InvalidMulliganCard(_) => 3016,
+            GameLogicError::MatchOver => 3017,
+        }
+    }
+
+    fn category(&self) -> ErrorCategory {
+        ErrorCategory::Game
+    }
+}
+
+impl ErrorCode for ServerInstanceError {
+    fn code(&self) -> u16 {
+        match self {
+            ServerInstanceError::PlaceHolderError => 4001,
+            ServerInstanceError::AlreadyInitialized => 4002,
+            ServerInstanceError::GameInstanceFail(_) => 4003,
+            ServerInstanceError::UnwrapFailed => 4004,
+        }
+    }
+
+    fn category(&self) -> ErrorCategory {
+        ErrorCategory::Server
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_envelope_from_game_error() {
+        let envelope = ErrorEnvelope::from(&GameLogicError::NotPlayerTurn);
+        assert_eq!(envelope.code, 3008);
+        assert_eq!(envelope.category, ErrorCategory::Game);
+        assert_eq!(envelope.message, "Not player's turn");
+        assert!(envelope.details.is_none());
+    }
+
+    #[test]
+    fn test_error_envelope_details() {
+        let envelope = ErrorEnvelope::from(&PlayerConnectionError::UnsupportedProtocolVersion(0));
+        let details: SupportedVersions =
+            serde_cbor::value::from_value(envelope.details.unwrap()).unwrap();
+        assert_eq!(
+            details.max_protocol_version,
+            SupportedVersions::default().max_protocol_version
+        );
+    }
+}
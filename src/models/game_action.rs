@@ -5,5 +5,8 @@ use serde::{Deserialize, Serialize};
 pub enum GameAction {
     DealDamage { target: String, amount: u32 },
     Heal { target: String, amount: u32 },
-    Summon { id: String, position: String }
+    Summon { id: String, position: String },
+    GainMana { target: String, amount: u32 },
+    SpendMana { target: String, amount: u32 },
+    LockMana { target: String, amount: u32 },
 }
\ No newline at end of file
@@ -0,0 +1,52 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Outcome of an applied `GameAction` or combat, sent to clients so they can animate it.
+///
+/// `target` is either a player id, for their hero, or the instance id of a creature on the
+/// board. `card_id` is the catalog id of a card.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+#[serde(tag = "type")]
+pub enum GameEvent {
+    Damaged {
+        target: String,
+        amount: u32,
+        health: i32,
+    },
+    Healed {
+        target: String,
+        amount: u32,
+        health: i32,
+    },
+    Summoned {
+        instance_id: String,
+        card_id: String,
+        owner_id: String,
+        position: usize,
+    },
+    Died {
+        instance_id: String,
+        owner_id: String,
+    },
+    /// The owner drew a card into their hand. The card itself is only revealed to them,
+    /// through their game state.
+    Drew { owner_id: String },
+    /// The owner drew a card while their hand was full, sending it to the graveyard.
+    Burned {
+        instance_id: String,
+        card_id: String,
+        owner_id: String,
+    },
+    /// The player had to draw from an empty deck, and their hero took their fatigue as damage.
+    Fatigued {
+        player_id: String,
+        damage: u32,
+        health: i32,
+    },
+    ManaChanged {
+        player_id: String,
+        mana: i32,
+        max_mana: i32,
+        overload: i32,
+    },
+}
@@ -0,0 +1,22 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Payload of a `Ping` packet, sent by either the server or the client.
+///
+/// Timestamps are milliseconds since the Unix epoch on the sender's clock.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, JsonSchema)]
+#[serde(default)]
+pub struct PingMessage {
+    pub sent_at: i64,
+}
+
+/// Payload of a `Pong` packet, answering a `PingMessage`.
+///
+/// Carries the original ping timestamp along with the answering side's receive and send times,
+/// so the pinging side can derive the round-trip time and clock offset.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, JsonSchema)]
+pub struct PongMessage {
+    pub ping_sent_at: i64,
+    pub received_at: i64,
+    pub sent_at: i64,
+}
@@ -1,13 +1,17 @@
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct InitServerRequest {
     pub match_id: String,
     pub match_type: String,
-    pub players: Vec<PreloadPlayer>
+    pub players: Vec<PreloadPlayer>,
+    /// Seeds the match's shuffles so that it can be replayed. A random seed is used if absent.
+    #[serde(default)]
+    pub seed: Option<u64>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct PreloadPlayer {
     pub id: String,
     pub deck_id: String,
@@ -0,0 +1,38 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+
+/// Why the match ended.
+///
+/// - `HeroDefeated` - A hero's health reached zero, from damage or fatigue. Both heroes
+///   falling together is a draw.
+/// - `Conceded` - A player gave up with a `Concede` action.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, JsonSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum MatchEndReason {
+    HeroDefeated,
+    Conceded,
+}
+
+/// Final result of the match, broadcast to both players with a `MatchEnded` packet.
+///
+/// `winner` is `None` when the match ended in a draw.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct MatchResult {
+    pub winner: Option<String>,
+    pub reason: MatchEndReason,
+    pub turn: u32,
+}
+
+impl Display for MatchResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.winner {
+            Some(winner) => write!(
+                f,
+                "`{winner}` won ({:?}) on turn {}",
+                self.reason, self.turn
+            ),
+            None => write!(f, "draw ({:?}) on turn {}", self.reason, self.turn),
+        }
+    }
+}
@@ -2,5 +2,10 @@ pub mod client_requests;
 pub mod http_response;
 pub mod settings;
 pub mod game_action;
+pub mod game_event;
 pub mod exit_code;
 pub mod init_server;
+pub mod heartbeat;
+pub mod action_response;
+pub mod error_response;
+pub mod match_result;
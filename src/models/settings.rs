@@ -8,4 +8,74 @@ pub struct Settings {
     pub card_server: String,
     #[serde(rename = "DECK_SERVER")]
     pub deck_server: String,
+    #[serde(
+        rename = "HEARTBEAT_INTERVAL_MS",
+        default = "default_heartbeat_interval"
+    )]
+    pub heartbeat_interval_ms: u64,
+    #[serde(rename = "HEARTBEAT_TIMEOUT_MS", default = "default_heartbeat_timeout")]
+    pub heartbeat_timeout_ms: u64,
+    #[serde(rename = "LATENCY_WARNING_MS", default = "default_latency_warning")]
+    pub latency_warning_ms: i64,
+    #[serde(
+        rename = "OUTBOUND_QUEUE_SIZE",
+        default = "default_outbound_queue_size"
+    )]
+    pub outbound_queue_size: usize,
+    #[serde(rename = "TLS_CERT_PATH", default)]
+    pub tls_cert_path: Option<String>,
+    #[serde(rename = "TLS_KEY_PATH", default)]
+    pub tls_key_path: Option<String>,
+    #[serde(rename = "WEBSOCKET_PORT", default)]
+    pub websocket_port: Option<u16>,
+    #[serde(rename = "MULLIGAN_TIMEOUT_MS", default = "default_mulligan_timeout")]
+    pub mulligan_timeout_ms: u64,
+}
+
+fn default_heartbeat_interval() -> u64 {
+    5000
+}
+
+fn default_heartbeat_timeout() -> u64 {
+    15000
+}
+
+fn default_latency_warning() -> i64 {
+    250
+}
+
+fn default_outbound_queue_size() -> usize {
+    64
+}
+
+fn default_mulligan_timeout() -> u64 {
+    30000
+}
+
+#[cfg(test)]
+impl Settings {
+    /// Initializes the global settings for tests, with heartbeat and mulligan timings short
+    /// enough to be waited for. Servers are never contacted.
+    ///
+    /// # Returns
+    /// The settings shared by every test of the process.
+    pub async fn init_for_tests() -> &'static Settings {
+        crate::SETTINGS
+            .get_or_init(|| async {
+                Settings {
+                    auth_server: "http://127.0.0.1:9".to_string(),
+                    card_server: "http://127.0.0.1:9".to_string(),
+                    deck_server: "http://127.0.0.1:9".to_string(),
+                    heartbeat_interval_ms: 20,
+                    heartbeat_timeout_ms: 100,
+                    latency_warning_ms: default_latency_warning(),
+                    outbound_queue_size: default_outbound_queue_size(),
+                    tls_cert_path: None,
+                    tls_key_path: None,
+                    websocket_port: None,
+                    mulligan_timeout_ms: 100,
+                }
+            })
+            .await
+    }
 }
@@ -1,16 +1,20 @@
 use super::protocol::Protocol;
 use crate::game::entity::player::Player;
+use crate::tcp::decoder::PacketDecoder;
+use crate::tcp::handshake::Capabilities;
 use crate::tcp::header::HeaderType;
+use crate::tcp::latency::LatencyStats;
+use crate::tcp::message::Message;
+use crate::tcp::outbound::{Outbound, OutboundQueue, OutboundReceiver};
 use crate::tcp::packet::Packet;
-use crate::{logger, utils::logger::Logger};
+use crate::tcp::transport::{self, BoxedTransport, TransportReader, TransportWriter};
+use crate::utils::checksum::ChecksumAlgorithm;
+use crate::{logger, utils::logger::Logger, SETTINGS};
+use std::time::{Duration, Instant};
 use std::{collections::VecDeque, net::SocketAddr, sync::Arc};
 use tokio::{
-    io::AsyncReadExt,
-    net::{
-        tcp::{OwnedReadHalf, OwnedWriteHalf},
-        TcpStream,
-    },
-    sync::RwLock,
+    io::{AsyncReadExt, AsyncWriteExt},
+    sync::{Mutex, RwLock},
 };
 
 /// Represents a connected client in the game server.
@@ -22,8 +26,16 @@ pub struct Client {
     pub player: Arc<RwLock<Player>>,
     pub connected: Arc<RwLock<bool>>,
     pub addr: Arc<RwLock<SocketAddr>>,
-    pub read_stream: Arc<RwLock<OwnedReadHalf>>,
-    pub write_stream: Arc<RwLock<OwnedWriteHalf>>,
+    pub read_stream: Arc<RwLock<TransportReader>>,
+    pub write_stream: Arc<RwLock<TransportWriter>>,
+    pub decoder: Arc<RwLock<PacketDecoder>>,
+    pub capabilities: Arc<RwLock<Capabilities>>,
+    pub next_sequence: Arc<RwLock<u32>>,
+    pub last_seen: Arc<RwLock<Instant>>,
+    pub connection_id: Arc<RwLock<u32>>,
+    pub latency: Arc<RwLock<LatencyStats>>,
+    pub outbound: Arc<OutboundQueue>,
+    pub outbound_receiver: Arc<Mutex<Option<OutboundReceiver>>>,
     pub missed_packets: Arc<RwLock<VecDeque<Packet>>>,
 }
 
@@ -31,22 +43,29 @@ impl Client {
     /// Creates a new `Client` instance from a TCP stream and address.
     ///
     /// Splits the stream into read/write halves and wraps all fields
-    /// in thread-safe containers for async access.
+    /// in thread-safe containers for async access. The outbound queue is
+    /// bounded by the `OUTBOUND_QUEUE_SIZE` setting.
     ///
     /// # Arguments
     /// - `stream`: The TCP stream from the accepted connection.
     /// - `addr`: The client's socket address.
+    /// - `decoder`: The decoder holding any bytes already read past the authentication packet.
+    /// - `capabilities`: The protocol version and features negotiated during authentication.
     /// - `rx`: A broadcast receiver for incoming packets.
     ///
     /// # Returns
     /// An `Arc<Client>` ready for use in async tasks.
     pub fn new(
-        read_stream: OwnedReadHalf,
-        write_stream: OwnedWriteHalf,
+        read_stream: TransportReader,
+        write_stream: TransportWriter,
         addr: SocketAddr,
+        decoder: PacketDecoder,
+        capabilities: Capabilities,
         protocol: Arc<Protocol>,
         player: Arc<RwLock<Player>>,
     ) -> Self {
+        let settings = SETTINGS.get().expect("Settings not initialized");
+        let (outbound, outbound_receiver) = OutboundQueue::new(settings.outbound_queue_size);
         Self {
             player,
             protocol,
@@ -54,21 +73,33 @@ impl Client {
             connected: Arc::new(RwLock::new(true)),
             read_stream: Arc::new(RwLock::new(read_stream)),
             write_stream: Arc::new(RwLock::new(write_stream)),
+            decoder: Arc::new(RwLock::new(decoder)),
+            capabilities: Arc::new(RwLock::new(capabilities)),
+            next_sequence: Arc::new(RwLock::new(1)),
+            last_seen: Arc::new(RwLock::new(Instant::now())),
+            connection_id: Arc::new(RwLock::new(0)),
+            latency: Arc::new(RwLock::new(LatencyStats::new())),
+            outbound: Arc::new(outbound),
+            outbound_receiver: Arc::new(Mutex::new(Some(outbound_receiver))),
             missed_packets: Arc::new(RwLock::new(VecDeque::new())),
         }
     }
 
     /// Handles the main lifecycle of a connected client.
     ///
-    /// - Logs connection and spawns a background game state update task.
-    /// - Reads data from the client in a loop, parses packets, and handles them.
-    /// - Verifies checksums and sends error responses if validation fails.
-    ///
-    /// Exits the loop (and drops the client) if the connection is closed, or an error occurs.
+    /// - Logs connection and spawns the background writer, game state update and heartbeat tasks.
+    /// - Listens to the client's stream until the connection is closed.
     pub async fn connect(self: Arc<Self>) {
-        let addr = self.addr.read().await;
+        let addr = *self.addr.read().await;
         logger!(DEBUG, "[CLIENT] Listening to `{addr}` (Authenticated)");
 
+        tokio::spawn({
+            let self_clone = Arc::clone(&self);
+            async move {
+                self_clone.write_outbound().await;
+            }
+        });
+
         tokio::spawn({
             let self_clone = Arc::clone(&self);
             async move {
@@ -76,79 +107,251 @@ impl Client {
             }
         });
 
+        tokio::spawn({
+            let self_clone = Arc::clone(&self);
+            async move {
+                self_clone.send_heartbeats().await;
+            }
+        });
+
+        self.listen().await;
+    }
+
+    /// Reads from the client's stream for as long as the connection is alive.
+    ///
+    /// - Reads data from the client in a loop and feeds it to the packet decoder.
+    /// - Handles every complete packet the decoder yields, keeping partial ones buffered.
+    /// - Verifies checksums and sends error responses if validation fails.
+    /// - Marks the client as disconnected when no traffic arrives within the heartbeat timeout,
+    ///   if its connection negotiated heartbeats. Older clients never answer pings, so they are
+    ///   only disconnected when the stream fails.
+    ///
+    /// Exits the loop if the connection is closed, times out, or an error occurs. Reads are
+    /// bounded by the heartbeat interval so that a disconnection flagged elsewhere is noticed promptly.
+    /// Also exits, without flagging a disconnection, once a reconnection replaces the stream.
+    async fn listen(self: Arc<Self>) {
+        let settings = SETTINGS.get().expect("Settings not initialized");
+        let interval = Duration::from_millis(settings.heartbeat_interval_ms);
+        let timeout = Duration::from_millis(settings.heartbeat_timeout_ms);
+        let addr = *self.addr.read().await;
+        let connection_id = *self.connection_id.read().await;
+
         let mut buffer = [0; 1024];
         while *self.connected.read().await {
             let mut read_stream_guard = self.read_stream.write().await;
-            let bytes_read = match read_stream_guard.read(&mut buffer).await {
-                Ok(0) => break,
-                Ok(n) => n,
-                Err(_) => break,
-            };
+            if *self.connection_id.read().await != connection_id {
+                return;
+            }
+
+            let bytes_read =
+                match tokio::time::timeout(interval, read_stream_guard.read(&mut buffer)).await {
+                    Ok(Ok(0)) => break,
+                    Ok(Ok(n)) => n,
+                    Ok(Err(_)) => break,
+                    Err(_) => {
+                        let heartbeat = self.capabilities.read().await.heartbeat;
+                        if heartbeat && self.last_seen.read().await.elapsed() > timeout {
+                            logger!(WARN, "[CLIENT] `{addr}` timed out");
+                            break;
+                        }
+                        continue;
+                    }
+                };
 
+            *self.last_seen.write().await = Instant::now();
+            let mut decoder = self.decoder.write().await;
+            decoder.extend(&buffer[..bytes_read]);
+            loop {
+                match decoder.next_packet() {
+                    Ok(Some(packet)) => {
+                        self.protocol
+                            .handle_incoming(Arc::clone(&self), packet)
+                            .await
+                    }
+                    Ok(None) => break,
+                    Err(error) => {
+                        logger!(ERROR, "[CLIENT] Invalid packet from `{addr}` ({error})");
+                        break;
+                    }
+                }
+            }
+        }
+
+        if *self.connected.read().await && *self.connection_id.read().await == connection_id {
+            self.protocol.disconnect(Arc::clone(&self)).await;
+        }
+    }
+
+    /// Periodically pings the client so that idle connections still produce traffic.
+    ///
+    /// Pings are only sent while the client is connected, and only if its connection negotiated
+    /// heartbeats. This function runs for the lifetime of the client.
+    async fn send_heartbeats(self: Arc<Self>) {
+        let settings = SETTINGS.get().expect("Settings not initialized");
+        let mut interval =
+            tokio::time::interval(Duration::from_millis(settings.heartbeat_interval_ms));
+        loop {
+            interval.tick().await;
+            if *self.connected.read().await && self.capabilities.read().await.heartbeat {
+                self.protocol.send_ping(Arc::clone(&self)).await;
+            }
+        }
+    }
+
+    /// Writes every packet pushed to the client's outbound queue, one at a time.
+    ///
+    /// This is the only task writing to the client's stream, so slow writes and retries never
+    /// block the tasks producing packets. It runs for the lifetime of the client.
+    async fn write_outbound(self: Arc<Self>) {
+        let Some(mut receiver) = self.outbound_receiver.lock().await.take() else {
+            return;
+        };
+
+        while let Some(outbound) = receiver.recv().await {
             self.protocol
-                .handle_incoming(Arc::clone(&self), &buffer[..bytes_read])
+                .write_outbound(Arc::clone(&self), outbound)
                 .await;
         }
     }
 
-    /// Listens to game state updates and sends them to the client.
+    /// Pushes the player's view of the game state to the client's outbound queue, right away
+    /// and then whenever the game state changes.
     ///
-    /// - If the client is disconnected, queues the game state packets.
-    /// - Sends missed packets if any are queued.
-    /// - Sends the current game state to the client.
+    /// Changes made while a view is being built are folded into the next one, and only the
+    /// latest game state is kept when the client falls behind. Game states pushed while the
+    /// client is disconnected are queued by the writer for a resend on reconnection.
     ///
-    /// This function runs in a loop and exits when the receiver is dropped.
+    /// This function runs for the lifetime of the client.
     async fn listen_to_game_state(self: Arc<Self>) {
-        let protocol_clone = Arc::clone(&self.protocol);
-        let transmitter_clone = Arc::clone(&protocol_clone.transmitter);
-        let mut receiver = transmitter_clone.lock().await.subscribe();
-        while let Ok(game_state) = receiver.recv().await {
-            if !*self.connected.read().await {
-                let addr = self.addr.read().await;
-                let mut missed_packets = self.missed_packets.write().await;
-                missed_packets.push_back(game_state);
-
-                if missed_packets.len() > 30 {
-                    missed_packets.pop_front();
+        let game_instance = Arc::clone(&self.protocol.game_instance);
+        let mut changes = game_instance.state_changed.subscribe();
+        changes.mark_changed();
+        while changes.changed().await.is_ok() {
+            let player_id = self.player.read().await.id.clone();
+            let game_state = game_instance.game_state.read().await;
+            match game_state.wrap_game_state(&player_id).await {
+                Ok(payload) => {
+                    let packet = Packet::new(HeaderType::GameState, &payload);
+                    let _ = self
+                        .protocol
+                        .queue_outbound(Arc::clone(&self), Outbound::GameState(packet))
+                        .await;
                 }
-
-                logger!(
-                    WARN,
-                    "[CLIENT] `{addr}` has {} game state packets in queue",
-                    &missed_packets.len()
-                );
-
-                continue;
+                Err(error) => logger!(
+                    ERROR,
+                    "[CLIENT] Unable to serialize the game state of `{player_id}` ({error})"
+                ),
             }
+        }
+    }
 
-            if self.missed_packets.read().await.len() > 0 {
-                let client_clone = Arc::clone(&self);
-                self.protocol.send_missed_packets(client_clone).await;
-            }
+    /// Assigns the next sequence id of this connection to a copy of the packet.
+    ///
+    /// # Arguments
+    /// - `packet`: The packet about to be sent or queued.
+    ///
+    /// # Returns
+    /// The packet with its header carrying the new sequence id.
+    pub async fn sequence_packet(&self, packet: &Packet) -> Packet {
+        let mut next_sequence = self.next_sequence.write().await;
+        let mut packet = packet.clone();
+        packet.header.sequence = Some(*next_sequence);
+        *next_sequence = next_sequence.wrapping_add(1);
+        packet
+    }
 
-            let client_clone = Arc::clone(&self);
-            let _ = self.protocol.send_packet(client_clone, &game_state).await;
+    /// Keeps a sequenced packet until the client acknowledges it, holding at most 30 packets.
+    ///
+    /// # Arguments
+    /// - `packet`: The sequenced packet to keep for a later resend.
+    pub async fn queue_packet(&self, packet: Packet) {
+        let mut missed_packets = self.missed_packets.write().await;
+        missed_packets.push_back(packet);
+
+        if missed_packets.len() > 30 {
+            missed_packets.pop_front();
         }
     }
 
+    /// Drops every queued packet up to and including the given sequence id.
+    ///
+    /// # Arguments
+    /// - `sequence`: The last sequence id received by the client.
+    pub async fn acknowledge(&self, sequence: u32) {
+        let mut missed_packets = self.missed_packets.write().await;
+        missed_packets.retain(|packet| packet.header.sequence.is_some_and(|s| s > sequence));
+    }
+
     /// Reconnects a client using a temporary client instance.
     ///
     /// - Updates the client's read/write streams, address, and connection status.
+    /// - Replaces the packet decoder with the one used during re-authentication.
+    /// - Replaces the capabilities with the ones negotiated by the new connection.
+    /// - Resets the heartbeat timeout and starts listening to the new stream.
     ///
     /// # Arguments
     /// - `temporary_client`: A `TemporaryClient` instance containing the new connection details.
-    pub async fn reconnect(self: Arc<Self>, temporary_client: TemporaryClient) {
-        let (read, write) = temporary_client.stream.into_split();
+    /// - `capabilities`: The protocol version and features negotiated by the reconnection request.
+    pub async fn reconnect(
+        self: Arc<Self>,
+        temporary_client: TemporaryClient,
+        capabilities: Capabilities,
+    ) {
+        let (read, write) = transport::split(temporary_client.stream);
+
+        {
+            let mut write_stream = self.write_stream.write().await;
+            let mut read_stream = self.read_stream.write().await;
+            let mut decoder = self.decoder.write().await;
+            let mut current_capabilities = self.capabilities.write().await;
+            let mut addr = self.addr.write().await;
+            let mut last_seen = self.last_seen.write().await;
+            let mut connection_id = self.connection_id.write().await;
+            let mut connected = self.connected.write().await;
 
-        let mut write_stream = self.write_stream.write().await;
-        let mut read_stream = self.read_stream.write().await;
-        let mut addr = self.addr.write().await;
-        let mut connected = self.connected.write().await;
+            *write_stream = write;
+            *read_stream = read;
+            *decoder = temporary_client.decoder;
+            *current_capabilities = capabilities;
+            *addr = temporary_client.addr;
+            *last_seen = Instant::now();
+            *connection_id = connection_id.wrapping_add(1);
+            *connected = true;
+        }
 
-        *write_stream = write;
-        *read_stream = read;
-        *addr = temporary_client.addr;
-        *connected = true;
+        tokio::spawn({
+            let self_clone = Arc::clone(&self);
+            async move {
+                self_clone.listen().await;
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+impl Client {
+    /// Creates a client playing as `player_id` over an in-memory transport, for tests.
+    ///
+    /// # Returns
+    /// The client, not listening yet, and the stream acting as the remote client.
+    pub async fn for_tests(
+        protocol: &Arc<Protocol>,
+        player_id: &str,
+        capabilities: Capabilities,
+    ) -> (Arc<Self>, tokio::io::DuplexStream) {
+        let (stream, remote) = transport::memory(4096);
+        let (read, write) = transport::split(stream);
+        let player = Arc::clone(&protocol.game_instance.connected_players.read().await[player_id]);
+        let client = Client::new(
+            read,
+            write,
+            "127.0.0.1:4000".parse().unwrap(),
+            PacketDecoder::new(),
+            capabilities,
+            Arc::clone(protocol),
+            player,
+        );
+        (Arc::new(client), remote)
     }
 }
 
@@ -164,8 +367,12 @@ pub struct TemporaryClient {
     pub addr: SocketAddr,
     /// The protocol instance used to handle communication with the client.
     pub protocol: Arc<Protocol>,
-    /// The TCP stream associated with the temporary client.
-    pub stream: TcpStream,
+    /// The transport associated with the temporary client (TCP, TLS, WebSocket or in-memory).
+    pub stream: BoxedTransport,
+    /// The decoder buffering bytes read during authentication.
+    pub decoder: PacketDecoder,
+    /// The integrity algorithm used by the client's authentication packet, used when none is negotiated.
+    pub checksum_algorithm: ChecksumAlgorithm,
 }
 
 impl TemporaryClient {
@@ -178,21 +385,26 @@ impl TemporaryClient {
     ///
     /// # Returns
     /// A new `TemporaryClient` instance.
-    pub async fn new(stream: TcpStream, addr: SocketAddr, protocol: Arc<Protocol>) -> Self {
+    pub async fn new(stream: BoxedTransport, addr: SocketAddr, protocol: Arc<Protocol>) -> Self {
         TemporaryClient {
             addr,
             stream,
             protocol,
+            decoder: PacketDecoder::new(),
+            checksum_algorithm: ChecksumAlgorithm::default(),
         }
     }
 
     /// Handles the lifecycle of a temporary client.
     ///
-    /// - Reads data from the client for authentication.
+    /// - Reads data from the client for authentication and buffers it until a full packet is available.
     /// - Parses the packet and determines if it's a `Connect` or `Reconnect` request.
-    /// - Calls the appropriate protocol handler for authentication.
+    /// - Keeps the packet's checksum algorithm as a fallback for the capability negotiation.
+    /// - Calls the appropriate protocol handler for authentication, which answers failed
+    ///   authentications with an `ERROR` packet.
     ///
-    /// Exits if the client sends invalid data or an error occurs.
+    /// Exits if the client sends invalid data, after answering it with an `ERROR` packet, or if
+    /// an error occurs.
     pub async fn handle_temp_client(mut self) {
         let mut buffer = [0; 1024];
         let addr = self.addr.clone();
@@ -208,31 +420,233 @@ impl TemporaryClient {
                 Ok(n) => n,
             };
 
-            match Packet::parse(&buffer[..bytes]) {
-                Ok(packet) => {
-                    if packet.header.header_type == HeaderType::Connect {
+            self.decoder.extend(&buffer[..bytes]);
+            loop {
+                let packet = match self.decoder.next_packet() {
+                    Ok(Some(packet)) => packet,
+                    Ok(None) => break,
+                    Err(error) => {
+                        logger!(ERROR, "[CLIENT] Invalid packet from `{addr}` ({error})");
+                        return self.reject(&Packet::error(HeaderType::ERROR, &error)).await;
+                    }
+                };
+
+                self.checksum_algorithm = packet.header.checksum_algorithm;
+                match Message::decode(&packet) {
+                    Ok(Message::Connect(request)) => {
                         let temp_arc = Arc::new(self);
                         let protocol = Arc::clone(&temp_arc.protocol);
-                        if let Err(error) = protocol.handle_connect(temp_arc, &packet).await {
+                        if let Err(error) = protocol.handle_connect(temp_arc, request).await {
                             logger!(ERROR, "[CLIENT] Could not authenticate `{addr}` ({error})");
                         };
-                        break;
-                    } else if packet.header.header_type == HeaderType::Reconnect {
+                        return;
+                    }
+                    Ok(Message::Reconnect(request)) => {
                         let temp_arc = Arc::new(self);
                         let protocol = Arc::clone(&temp_arc.protocol);
-                        if let Err(error) = protocol.handle_reconnect(temp_arc, &packet).await {
+                        if let Err(error) = protocol.handle_reconnect(temp_arc, request).await {
                             logger!(ERROR, "[CLIENT] Could not authenticate `{addr}` ({error})");
                         } else {
                             logger!(INFO, "[CLIENT] `{addr}` has been reconnected as `todo`")
                         }
-                        break;
+                        return;
+                    }
+                    Ok(_) => {}
+                    Err(error) => {
+                        logger!(ERROR, "[CLIENT] Invalid message from `{addr}` ({error})");
+                        return self.reject(&Packet::error(HeaderType::ERROR, &error)).await;
                     }
-                }
-                Err(error) => {
-                    logger!(ERROR, "[CLIENT] Invalid packet from `{addr}` ({error})");
-                    return;
                 }
             }
         }
     }
+
+    /// Sends a packet to the temporary client and closes the connection.
+    ///
+    /// Used when the client is rejected before being promoted to a `Client`.
+    ///
+    /// # Arguments
+    /// - `packet`: The packet explaining why the client was rejected.
+    pub async fn reject(mut self, packet: &Packet) {
+        let _ = self.stream.write_all(&packet.wrap_packet()).await;
+        let _ = self.stream.flush().await;
+        let _ = self.stream.shutdown().await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::client_requests::{
+        ConnectionRequest, ReconnectionRequest, RequestedCapabilities,
+    };
+    use crate::models::error_response::{ErrorCategory, ErrorEnvelope};
+    use crate::models::heartbeat::{PingMessage, PongMessage};
+    use crate::tcp::handshake::PROTOCOL_VERSION;
+    use serde_cbor::Value;
+    use std::collections::BTreeMap;
+
+    /// Returns the turn phase of the game state carried by a `GameState` packet.
+    fn phase_of(packet: &Packet) -> String {
+        let state: BTreeMap<Value, Value> = serde_cbor::from_slice(&packet.payload).unwrap();
+        match &state[&Value::Text("phase".to_string())] {
+            Value::Text(phase) => phase.clone(),
+            phase => panic!("Unexpected phase {phase:?}"),
+        }
+    }
+
+    /// Waits until the client is flagged as disconnected, for at most a second.
+    async fn disconnected(client: &Client) -> bool {
+        let wait = async {
+            while *client.connected.read().await {
+                tokio::task::yield_now().await;
+            }
+        };
+        tokio::time::timeout(Duration::from_secs(1), wait)
+            .await
+            .is_ok()
+    }
+
+    #[tokio::test]
+    async fn test_temporary_client_rejects_version() {
+        let protocol = Arc::new(Protocol::for_tests().await);
+        let (stream, mut remote) = transport::memory(4096);
+        let addr = "127.0.0.1:4000".parse().unwrap();
+        let temporary_client = TemporaryClient::new(stream, addr, protocol).await;
+        tokio::spawn(temporary_client.handle_temp_client());
+
+        let request = ConnectionRequest {
+            protocol_version: Some(0),
+            ..ConnectionRequest::default()
+        };
+        let payload = serde_cbor::to_vec(&request).unwrap();
+        let packet = Packet::new(HeaderType::Connect, &payload).wrap_packet();
+        remote.write_all(&packet).await.unwrap();
+
+        // Unsupported versions are answered before contacting the authentication server
+        transport::receive(&mut remote, HeaderType::UnsupportedProtocolVersion).await;
+        let mut rest = Vec::new();
+        remote.read_to_end(&mut rest).await.unwrap();
+        assert!(rest.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_temporary_client_rejects_invalid_message() {
+        let protocol = Arc::new(Protocol::for_tests().await);
+        let (stream, mut remote) = transport::memory(4096);
+        let addr = "127.0.0.1:4002".parse().unwrap();
+        let temporary_client = TemporaryClient::new(stream, addr, protocol).await;
+        tokio::spawn(temporary_client.handle_temp_client());
+
+        let packet = Packet::new(HeaderType::Connect, b"not cbor").wrap_packet();
+        remote.write_all(&packet).await.unwrap();
+
+        let packet = transport::receive(&mut remote, HeaderType::ERROR).await;
+        let envelope: ErrorEnvelope = serde_cbor::from_slice(&packet.payload).unwrap();
+        assert_eq!(envelope.category, ErrorCategory::Protocol);
+        let mut rest = Vec::new();
+        remote.read_to_end(&mut rest).await.unwrap();
+        assert!(rest.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_temporary_client_rejects_failed_authentication() {
+        let protocol = Arc::new(Protocol::for_tests().await);
+        let (stream, mut remote) = transport::memory(4096);
+        let addr = "127.0.0.1:4003".parse().unwrap();
+        let temporary_client = TemporaryClient::new(stream, addr, protocol).await;
+        tokio::spawn(temporary_client.handle_temp_client());
+
+        // The authentication server of the test settings is unreachable
+        let payload = serde_cbor::to_vec(&ReconnectionRequest::default()).unwrap();
+        let packet = Packet::new(HeaderType::Reconnect, &payload).wrap_packet();
+        remote.write_all(&packet).await.unwrap();
+
+        let packet = transport::receive(&mut remote, HeaderType::ERROR).await;
+        let envelope: ErrorEnvelope = serde_cbor::from_slice(&packet.payload).unwrap();
+        assert_eq!(envelope.category, ErrorCategory::Connection);
+        let mut rest = Vec::new();
+        remote.read_to_end(&mut rest).await.unwrap();
+        assert!(rest.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_reconnect_replaces_stream() {
+        let protocol = Arc::new(Protocol::for_tests().await);
+        let (client, _remote) = Client::for_tests(&protocol, "red", Capabilities::default()).await;
+        tokio::spawn(Arc::clone(&client).connect());
+
+        let (stream, mut remote) = transport::memory(4096);
+        let addr = "127.0.0.1:4001".parse().unwrap();
+        let temporary_client = TemporaryClient::new(stream, addr, Arc::clone(&protocol)).await;
+        Arc::clone(&client)
+            .reconnect(temporary_client, Capabilities::default())
+            .await;
+        assert_eq!(*client.addr.read().await, addr);
+
+        // The new stream is listened to and written to
+        let packet = Packet::new(HeaderType::Disconnect, b"").wrap_packet();
+        remote.write_all(&packet).await.unwrap();
+        transport::receive(&mut remote, HeaderType::Disconnect).await;
+        assert!(disconnected(&client).await);
+    }
+
+    #[tokio::test]
+    async fn test_ping_answered_with_pong() {
+        let protocol = Arc::new(Protocol::for_tests().await);
+        let (client, mut remote) =
+            Client::for_tests(&protocol, "red", Capabilities::default()).await;
+        tokio::spawn(Arc::clone(&client).connect());
+
+        let ping = serde_cbor::to_vec(&PingMessage { sent_at: 42 }).unwrap();
+        let packet = Packet::new(HeaderType::Ping, &ping).wrap_packet();
+        remote.write_all(&packet).await.unwrap();
+
+        let pong = transport::receive(&mut remote, HeaderType::Pong).await;
+        let pong: PongMessage = serde_cbor::from_slice(&pong.payload).unwrap();
+        assert_eq!(pong.ping_sent_at, 42);
+        assert!(pong.sent_at >= pong.received_at);
+    }
+
+    #[tokio::test]
+    async fn test_heartbeat_timeout() {
+        let protocol = Arc::new(Protocol::for_tests().await);
+        let requested = RequestedCapabilities::default();
+        let capabilities =
+            Capabilities::negotiate(Some(PROTOCOL_VERSION), &requested, ChecksumAlgorithm::Xor)
+                .unwrap();
+        let (client, mut remote) = Client::for_tests(&protocol, "red", capabilities).await;
+        tokio::spawn(Arc::clone(&client).connect());
+
+        // Clients that never answer the server's pings are flagged as disconnected
+        transport::receive(&mut remote, HeaderType::Ping).await;
+        assert!(disconnected(&client).await);
+    }
+
+    #[tokio::test]
+    async fn test_legacy_client_not_timed_out() {
+        let protocol = Arc::new(Protocol::for_tests().await);
+        let settings = SETTINGS.get().unwrap();
+        let (client, _remote) = Client::for_tests(&protocol, "red", Capabilities::default()).await;
+        tokio::spawn(Arc::clone(&client).connect());
+
+        // Legacy clients are neither pinged nor timed out while idle
+        tokio::time::sleep(Duration::from_millis(settings.heartbeat_timeout_ms * 3)).await;
+        assert!(*client.connected.read().await);
+    }
+
+    #[tokio::test]
+    async fn test_game_state_pushed() {
+        let protocol = Arc::new(Protocol::for_tests().await);
+        let (client, mut remote) =
+            Client::for_tests(&protocol, "red", Capabilities::default()).await;
+        tokio::spawn(Arc::clone(&client).connect());
+
+        // The current game state is pushed on connection, then after every change
+        let packet = transport::receive(&mut remote, HeaderType::GameState).await;
+        assert_eq!(phase_of(&packet), "START");
+        Arc::clone(&protocol.game_instance).start_game().await;
+        let packet = transport::receive(&mut remote, HeaderType::GameState).await;
+        assert_eq!(phase_of(&packet), "MULLIGAN");
+    }
 }
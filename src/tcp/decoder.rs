@@ -0,0 +1,137 @@
+use crate::tcp::header::{Header, HEADER_SIZE, MAX_PAYLOAD_LENGTH};
+use crate::tcp::packet::Packet;
+use crate::utils::errors::ProtocolError;
+
+/// Reassembles packets from a raw TCP byte stream.
+///
+/// TCP does not preserve message boundaries, so a single `read()` may contain
+/// part of a packet or several packets back to back. The decoder buffers the
+/// incoming bytes and uses `Header::payload_length` to know when a full packet
+/// is available.
+#[derive(Default)]
+pub struct PacketDecoder {
+    buffer: Vec<u8>,
+}
+
+impl PacketDecoder {
+    /// Creates an empty `PacketDecoder`.
+    pub fn new() -> Self {
+        Self { buffer: Vec::new() }
+    }
+
+    /// Appends bytes read from the stream to the internal buffer.
+    ///
+    /// # Arguments
+    /// - `bytes`: The bytes returned by the last `read()` call.
+    pub fn extend(&mut self, bytes: &[u8]) {
+        self.buffer.extend_from_slice(bytes);
+    }
+
+    /// Attempts to take the next complete packet out of the buffer.
+    ///
+    /// # Returns
+    /// - `Ok(Some(Packet))`: If a full packet was buffered.
+    /// - `Ok(None)`: If more bytes are needed to complete the next packet.
+    /// - `Err(ProtocolError)`: If the buffered header is invalid. The buffer is cleared,
+    ///   since the stream can no longer be resynchronized.
+    pub fn next_packet(&mut self) -> Result<Option<Packet>, ProtocolError> {
+        if self.buffer.len() < HEADER_SIZE {
+            return Ok(None);
+        }
+
+        let header_size = Header::required_size(&self.buffer);
+        if self.buffer.len() < header_size {
+            return Ok(None);
+        }
+
+        let header = match Header::from_bytes(&self.buffer[..header_size]) {
+            Ok(header) => header,
+            Err(error) => {
+                self.buffer.clear();
+                return Err(error);
+            }
+        };
+
+        if header.payload_length > MAX_PAYLOAD_LENGTH {
+            self.buffer.clear();
+            return Err(ProtocolError::InvalidHeaderError(format!(
+                "Payload length exceeds limit: {}",
+                header.payload_length
+            )));
+        }
+
+        let packet_length = header_size + header.payload_length as usize;
+        if self.buffer.len() < packet_length {
+            return Ok(None);
+        }
+
+        let packet = Packet::parse(&self.buffer[..packet_length]);
+        self.buffer.drain(..packet_length);
+        packet.map(Some)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tcp::header::HeaderType;
+
+    #[test]
+    fn test_decoder_partial_packet() {
+        let packet = Packet::new(HeaderType::PlayCard, b"split payload").wrap_packet();
+        let mut decoder = PacketDecoder::new();
+
+        // Only the header and part of the payload arrived
+        decoder.extend(&packet[..8]);
+        assert!(decoder.next_packet().unwrap().is_none());
+
+        // The rest of the payload arrives in a second read
+        decoder.extend(&packet[8..]);
+        let decoded = decoder.next_packet().unwrap().unwrap();
+        assert_eq!(decoded.header.header_type, HeaderType::PlayCard);
+        assert_eq!(&*decoded.payload, b"split payload");
+        assert_eq!(decoder.buffer.len(), 0);
+    }
+
+    #[test]
+    fn test_decoder_coalesced_packets() {
+        let mut bytes = Packet::new(HeaderType::Ping, b"").wrap_packet().to_vec();
+        bytes.extend_from_slice(&Packet::new(HeaderType::PlayCard, b"action").wrap_packet());
+        let mut decoder = PacketDecoder::new();
+        decoder.extend(&bytes);
+
+        // Both packets must come out of a single read
+        let first = decoder.next_packet().unwrap().unwrap();
+        let second = decoder.next_packet().unwrap().unwrap();
+        assert_eq!(first.header.header_type, HeaderType::Ping);
+        assert_eq!(second.header.header_type, HeaderType::PlayCard);
+        assert_eq!(&*second.payload, b"action");
+        assert!(decoder.next_packet().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_decoder_extended_packet() {
+        let payload = vec![0x2A; 40_000];
+        let packet = Packet::new(HeaderType::GameState, &payload).wrap_packet();
+        let mut decoder = PacketDecoder::new();
+
+        // The first read stops before the extended length is complete
+        decoder.extend(&packet[..8]);
+        assert!(decoder.next_packet().unwrap().is_none());
+
+        decoder.extend(&packet[8..]);
+        let decoded = decoder.next_packet().unwrap().unwrap();
+        assert_eq!(decoded.header.payload_length, 40_000);
+        assert_eq!(decoded.payload.len(), 40_000);
+    }
+
+    #[test]
+    fn test_decoder_invalid_header() {
+        let mut decoder = PacketDecoder::new();
+        decoder.extend(&[0x77, 0x00, 0x00, 0x00, 0x00, 0x0A]);
+
+        // An unknown header type cannot be recovered from and drops the buffer
+        assert!(decoder.next_packet().is_err());
+        assert_eq!(decoder.buffer.len(), 0);
+    }
+}
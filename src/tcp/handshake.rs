@@ -0,0 +1,178 @@
+use crate::models::client_requests::RequestedCapabilities;
+use crate::tcp::header::{MAX_LEGACY_PAYLOAD_LENGTH, MAX_PAYLOAD_LENGTH};
+use crate::utils::checksum::ChecksumAlgorithm;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Latest protocol version spoken by the server.
+///
+/// - `1` - Legacy layout: 6-byte header, XOR checksum, no handshake.
+/// - `2` - Extended payload length, CRC checksums and capability negotiation.
+/// - `3` - Sequence ids on server packets and client acknowledgements.
+/// - `4` - Optional payload compression.
+/// - `5` - Server heartbeats, answered with `Pong`, and the inactivity timeout.
+pub const PROTOCOL_VERSION: u16 = 5;
+/// Oldest protocol version the server still accepts.
+pub const MIN_PROTOCOL_VERSION: u16 = 1;
+/// Version assumed for clients that do not send one in their `Connect`/`Reconnect` request.
+const LEGACY_PROTOCOL_VERSION: u16 = 1;
+/// First version whose server packets carry a sequence id.
+const SEQUENCING_PROTOCOL_VERSION: u16 = 3;
+/// First version able to compress payloads.
+const COMPRESSION_PROTOCOL_VERSION: u16 = 4;
+/// First version answering server pings, and so subject to the inactivity timeout.
+const HEARTBEAT_PROTOCOL_VERSION: u16 = 5;
+
+/// The protocol version and feature set agreed upon for a single connection.
+///
+/// Sent back to the client as the payload of the `Connect`/`Reconnect` answer.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct Capabilities {
+    pub protocol_version: u16,
+    pub compression: bool,
+    pub checksum_algorithm: ChecksumAlgorithm,
+    pub max_payload_length: u32,
+    pub sequencing: bool,
+    pub heartbeat: bool,
+}
+
+impl Default for Capabilities {
+    /// Capabilities of a legacy client, used until a handshake says otherwise.
+    fn default() -> Self {
+        Self {
+            protocol_version: LEGACY_PROTOCOL_VERSION,
+            compression: false,
+            checksum_algorithm: ChecksumAlgorithm::Xor,
+            max_payload_length: MAX_LEGACY_PAYLOAD_LENGTH,
+            sequencing: false,
+            heartbeat: false,
+        }
+    }
+}
+
+/// Payload of an `UnsupportedProtocolVersion` packet, telling the client which versions it may retry with.
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+pub struct SupportedVersions {
+    pub min_protocol_version: u16,
+    pub max_protocol_version: u16,
+}
+
+impl Default for SupportedVersions {
+    fn default() -> Self {
+        Self {
+            min_protocol_version: MIN_PROTOCOL_VERSION,
+            max_protocol_version: PROTOCOL_VERSION,
+        }
+    }
+}
+
+impl Capabilities {
+    /// Negotiates the capabilities of a connection from the client's request.
+    ///
+    /// - The agreed version is the lowest between the client's and the server's.
+    /// - Legacy clients keep the XOR checksum and the 2-byte payload length.
+    /// - The checksum is the first algorithm listed by the client, falling back to the one used by
+    ///   its authentication packet.
+    /// - Compression is enabled from `COMPRESSION_PROTOCOL_VERSION` onwards, if the client asked for it.
+    /// - Sequencing is enabled from `SEQUENCING_PROTOCOL_VERSION` onwards.
+    /// - Heartbeats are enabled from `HEARTBEAT_PROTOCOL_VERSION` onwards.
+    ///
+    /// # Arguments
+    /// * `protocol_version` - The version sent by the client, if any.
+    /// * `requested` - The features the client asked for.
+    /// * `packet_algorithm` - The checksum algorithm of the client's authentication packet.
+    ///
+    /// # Returns
+    /// * `Some(Capabilities)` - The agreed feature set.
+    /// * `None` - If the client's version is older than `MIN_PROTOCOL_VERSION`.
+    pub fn negotiate(
+        protocol_version: Option<u16>,
+        requested: &RequestedCapabilities,
+        packet_algorithm: ChecksumAlgorithm,
+    ) -> Option<Self> {
+        let client_version = protocol_version.unwrap_or(LEGACY_PROTOCOL_VERSION);
+        let agreed_version = client_version.min(PROTOCOL_VERSION);
+        if agreed_version < MIN_PROTOCOL_VERSION {
+            return None;
+        }
+
+        if agreed_version == LEGACY_PROTOCOL_VERSION {
+            return Some(Capabilities::default());
+        }
+
+        let checksum_algorithm = requested
+            .checksum_algorithms
+            .first()
+            .copied()
+            .unwrap_or(packet_algorithm);
+
+        let max_payload_length = requested
+            .max_payload_length
+            .map_or(MAX_PAYLOAD_LENGTH, |length| length.min(MAX_PAYLOAD_LENGTH));
+
+        Some(Self {
+            protocol_version: agreed_version,
+            compression: requested.compression && agreed_version >= COMPRESSION_PROTOCOL_VERSION,
+            checksum_algorithm,
+            max_payload_length,
+            sequencing: agreed_version >= SEQUENCING_PROTOCOL_VERSION,
+            heartbeat: agreed_version >= HEARTBEAT_PROTOCOL_VERSION,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_negotiate_legacy_client() {
+        let requested = RequestedCapabilities::default();
+        let capabilities = Capabilities::negotiate(None, &requested, ChecksumAlgorithm::Xor);
+        // Clients without a version are treated as the legacy layout
+        assert_eq!(capabilities, Some(Capabilities::default()));
+    }
+
+    #[test]
+    fn test_negotiate_current_client() {
+        let requested = RequestedCapabilities {
+            compression: true,
+            checksum_algorithms: vec![ChecksumAlgorithm::Crc32, ChecksumAlgorithm::Crc16],
+            max_payload_length: Some(u32::MAX),
+        };
+        let capabilities = Capabilities::negotiate(
+            Some(PROTOCOL_VERSION + 1),
+            &requested,
+            ChecksumAlgorithm::Xor,
+        )
+        .unwrap();
+        // Newer clients are downgraded to the server's version and limits
+        assert_eq!(capabilities.protocol_version, PROTOCOL_VERSION);
+        assert_eq!(capabilities.checksum_algorithm, ChecksumAlgorithm::Crc32);
+        assert_eq!(capabilities.max_payload_length, MAX_PAYLOAD_LENGTH);
+        assert!(capabilities.compression);
+        assert!(capabilities.sequencing);
+        assert!(capabilities.heartbeat);
+    }
+
+    #[test]
+    fn test_negotiate_compression_version() {
+        let requested = RequestedCapabilities {
+            compression: true,
+            ..RequestedCapabilities::default()
+        };
+        let capabilities =
+            Capabilities::negotiate(Some(3), &requested, ChecksumAlgorithm::Crc16).unwrap();
+        // Clients predating compression never receive compressed payloads
+        assert!(!capabilities.compression);
+        // nor pings they would not answer
+        assert!(!capabilities.heartbeat);
+    }
+
+    #[test]
+    fn test_negotiate_unsupported_version() {
+        let requested = RequestedCapabilities::default();
+        let capabilities = Capabilities::negotiate(Some(0), &requested, ChecksumAlgorithm::Xor);
+        assert!(capabilities.is_none());
+    }
+}
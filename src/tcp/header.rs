@@ -1,33 +1,64 @@
-use crate::utils::checksum::Checksum;
+use crate::utils::checksum::{Checksum, ChecksumAlgorithm};
 use crate::utils::errors::ProtocolError;
 use std::fmt::Display;
 
+/// Size in bytes of the legacy header, used for payloads up to `MAX_LEGACY_PAYLOAD_LENGTH`.
+pub const HEADER_SIZE: usize = 6;
+/// Size in bytes of the payload length appended by the extended header revision.
+pub const EXTENDED_LENGTH_SIZE: usize = 4;
+/// Size in bytes of the lower checksum half appended when using `ChecksumAlgorithm::Crc32`.
+pub const CHECKSUM_EXTENSION_SIZE: usize = 2;
+/// Size in bytes of the sequence id appended when `SEQUENCED_FLAG` is set.
+pub const SEQUENCE_SIZE: usize = 4;
+/// Bits of the end byte holding the `ChecksumAlgorithm`.
+const CHECKSUM_ALGORITHM_MASK: u8 = 0x0F;
+/// End byte flag signalling that a 4-byte sequence id follows the checksum extension.
+pub const SEQUENCED_FLAG: u8 = 0x10;
+/// End byte flag signalling that the payload is DEFLATE-compressed.
+pub const COMPRESSED_FLAG: u8 = 0x20;
+/// Largest payload that still fits the 2-byte length field read as `i16` by old clients.
+pub const MAX_LEGACY_PAYLOAD_LENGTH: u32 = i16::MAX as u32;
+/// Largest payload accepted by the server, regardless of the header revision.
+pub const MAX_PAYLOAD_LENGTH: u32 = 1024 * 1024;
+/// Value of the 2-byte length field signalling that the real length follows the delimiter.
+pub const EXTENDED_LENGTH_MARKER: u16 = 0xFFFF;
+
 /// Represents the type of message in a protocol packet.
 ///
 /// Each variant maps to a specific `u8` value used during transmission.
 ///
 /// # Variants
 ///
-/// ## General (0x00–0x03):
+/// ## General (0x00–0x05):
 /// - `Disconnect` - Client is disconnecting.
 /// - `Connect` - Client is initiating a connection.
-/// - `Ping` - Client is sending a ping to the server.
+/// - `Ping` - Heartbeat sent by the client or the server.
 /// - `Reconnect` - Client is attempting to reconnect.
+/// - `Ack` - Client acknowledges every sequenced packet up to a sequence id.
+/// - `Pong` - Answer to a `Ping`, sent by either side.
 ///
 /// ## Game State (0x10):
 /// - `GameState` - Server is sending the current game state.
 ///
-/// ## Actions (0x11–0x12):
+/// ## Actions (0x11–0x16):
 /// - `PlayCard` - Client is playing a card.
 /// - `AttackPlayer` - Client is attacking another player.
+/// - `InitServer` - Match maker is initializing the match.
+/// - `EndTurn` - Client is ending their turn.
+/// - `Mulligan` - Client is returning cards from their opening hand.
+/// - `Concede` - Client is giving up the match.
+///
+/// ## Match End (0x17):
+/// - `MatchEnded` - Server is announcing the result of the match.
 ///
-/// ## Errors (0xFA–0xFF):
+/// ## Errors (0xF0–0xFF):
 /// - `InvalidHeader` - Malformed or unrecognized header.
 /// - `AlreadyConnected` - Client is already connected.
 /// - `InvalidPlayerData` - Malformed or missing player data.
 /// - `InvalidChecksum` - Payload failed checksum validation.
 /// - `FailedToConnectPlayer` - Server failed to connect the player.
 /// - `InvalidPacketPayload` - Packet payload is invalid.
+/// - `UnsupportedProtocolVersion` - Client's protocol version is not supported.
 /// - `ERROR` - Generic error.
 #[repr(u8)]
 #[derive(Debug, Clone, PartialEq)]
@@ -36,12 +67,19 @@ pub enum HeaderType {
     Connect = 0x01,
     Ping = 0x02,
     Reconnect = 0x03,
-    
+    Ack = 0x04,
+    Pong = 0x05,
+
     GameState = 0x10,
 
     PlayCard = 0x11,
     AttackPlayer = 0x12,
     InitServer = 0x13,
+    EndTurn = 0x14,
+    Mulligan = 0x15,
+    Concede = 0x16,
+
+    MatchEnded = 0x17,
 
     InvalidHeader = 0xFA,
     AlreadyConnected = 0xFB,
@@ -49,6 +87,7 @@ pub enum HeaderType {
     InvalidChecksum = 0xFD,
     FailedToConnectPlayer = 0xF0,
     InvalidPacketPayload = 0xF1,
+    UnsupportedProtocolVersion = 0xF2,
     ERROR = 0xFE,
 }
 
@@ -66,9 +105,15 @@ impl Display for HeaderType {
             HeaderType::Connect => String::from("CONNECT"),
             HeaderType::Reconnect => String::from("RECONNECT"),
             HeaderType::Ping => String::from("PING"),
+            HeaderType::Ack => String::from("ACK"),
+            HeaderType::Pong => String::from("PONG"),
 
             HeaderType::PlayCard => String::from("PLAY_CARD"),
             HeaderType::AttackPlayer => String::from("ATTACK_PLAYER"),
+            HeaderType::EndTurn => String::from("END_TURN"),
+            HeaderType::Mulligan => String::from("MULLIGAN"),
+            HeaderType::Concede => String::from("CONCEDE"),
+            HeaderType::MatchEnded => String::from("MATCH_ENDED"),
 
             HeaderType::InvalidHeader => String::from("INVALID_HEADER"),
             HeaderType::AlreadyConnected => String::from("ALREADY_CONNECTED"),
@@ -76,6 +121,7 @@ impl Display for HeaderType {
             HeaderType::InvalidChecksum => String::from("INVALID_CHECKSUM"),
             HeaderType::FailedToConnectPlayer => String::from("FAILED_TO_CONNECT_PLAYER"),
             HeaderType::InvalidPacketPayload => String::from("INVALID_PACKET_PAYLOAD"),
+            HeaderType::UnsupportedProtocolVersion => String::from("UNSUPPORTED_PROTOCOL_VERSION"),
             HeaderType::ERROR => String::from("ERROR"),
             HeaderType::InitServer => String::from("INIT_SERVER"),
 
@@ -105,11 +151,17 @@ impl TryFrom<u8> for HeaderType {
             0x01 => Ok(HeaderType::Connect),
             0x02 => Ok(HeaderType::Ping),
             0x03 => Ok(HeaderType::Reconnect),
+            0x04 => Ok(HeaderType::Ack),
+            0x05 => Ok(HeaderType::Pong),
 
             0x10 => Ok(HeaderType::GameState),
             0x11 => Ok(HeaderType::PlayCard),
             0x12 => Ok(HeaderType::AttackPlayer),
             0x13 => Ok(HeaderType::InitServer),
+            0x14 => Ok(HeaderType::EndTurn),
+            0x15 => Ok(HeaderType::Mulligan),
+            0x16 => Ok(HeaderType::Concede),
+            0x17 => Ok(HeaderType::MatchEnded),
 
             0xFA => Ok(HeaderType::InvalidHeader),
             0xFB => Ok(HeaderType::AlreadyConnected),
@@ -117,27 +169,36 @@ impl TryFrom<u8> for HeaderType {
             0xFD => Ok(HeaderType::InvalidChecksum),
             0xF0 => Ok(HeaderType::FailedToConnectPlayer),
             0xF1 => Ok(HeaderType::InvalidPacketPayload),
+            0xF2 => Ok(HeaderType::UnsupportedProtocolVersion),
             0xFE => Ok(HeaderType::ERROR),
             _ => Err(()),
         }
     }
 }
 
-/// Represents a fixed-size protocol header for game packet transmission.
+/// Represents a protocol header for game packet transmission.
 ///
 /// Contains the message type, payload length, and a checksum for validation.
-/// Serialized as 6 bytes total when sent over the network.
+/// Serialized as 6 bytes when the payload fits the legacy 2-byte length field, a 16-bit
+/// checksum is used and the packet is not sequenced. The lower half of the end byte identifies
+/// the `ChecksumAlgorithm` and the upper half holds flags. CRC-32 appends the lower half of the
+/// checksum, sequenced packets append their sequence id and payloads over
+/// `MAX_LEGACY_PAYLOAD_LENGTH` append a 4-byte length. Compressed packets set `COMPRESSED_FLAG`,
+/// in which case the length is the one of the compressed payload.
 #[derive(Clone)]
 pub struct Header {
-    pub checksum: i16,
-    pub payload_length: i16,
+    pub checksum: u32,
+    pub checksum_algorithm: ChecksumAlgorithm,
+    pub sequence: Option<u32>,
+    pub compressed: bool,
+    pub payload_length: u32,
     pub header_type: HeaderType,
 }
 
 impl Header {
     /// Creates a new `PacketHeader` from the given message type and payload.
     ///
-    /// Calculates the checksum and payload length automatically.
+    /// Calculates the checksum and payload length automatically, using the legacy XOR checksum.
     ///
     /// # Arguments
     /// - `header_type`: The type of the message (e.g., `Connect`, `Disconnect`).
@@ -146,37 +207,129 @@ impl Header {
     /// # Returns
     /// A new `Header` instance with the calculated checksum and payload length.
     pub fn new(header_type: HeaderType, payload: &[u8]) -> Self {
+        Header::with_algorithm(header_type, payload, ChecksumAlgorithm::default())
+    }
+
+    /// Creates a new `PacketHeader` whose checksum is computed with the given algorithm.
+    ///
+    /// # Arguments
+    /// - `header_type`: The type of the message (e.g., `Connect`, `Disconnect`).
+    /// - `payload`: The payload data for the packet.
+    /// - `checksum_algorithm`: The integrity algorithm used by the connection.
+    ///
+    /// # Returns
+    /// A new `Header` instance with the calculated checksum and payload length.
+    pub fn with_algorithm(
+        header_type: HeaderType,
+        payload: &[u8],
+        checksum_algorithm: ChecksumAlgorithm,
+    ) -> Self {
         Self {
-            checksum: Checksum::new(payload) as i16,
-            payload_length: payload.len() as i16,
+            checksum: Checksum::compute(&checksum_algorithm, payload),
+            checksum_algorithm,
+            sequence: None,
+            compressed: false,
+            payload_length: payload.len() as u32,
             header_type,
         }
     }
 
-    /// Serializes the header into a fixed-size byte array.
+    /// Returns the flags carried in the upper half of the end byte.
+    fn flags(&self) -> u8 {
+        let mut flags = 0;
+        if self.sequence.is_some() {
+            flags |= SEQUENCED_FLAG;
+        }
+        if self.compressed {
+            flags |= COMPRESSED_FLAG;
+        }
+        flags
+    }
+
+    /// Returns the serialized size of this header.
+    pub fn size(&self) -> usize {
+        let mut size = HEADER_SIZE;
+        if self.checksum_algorithm == ChecksumAlgorithm::Crc32 {
+            size += CHECKSUM_EXTENSION_SIZE;
+        }
+        if self.sequence.is_some() {
+            size += SEQUENCE_SIZE;
+        }
+        if self.payload_length > MAX_LEGACY_PAYLOAD_LENGTH {
+            size += EXTENDED_LENGTH_SIZE;
+        }
+        size
+    }
+
+    /// Determines the size of the header starting at the given bytes.
+    ///
+    /// Only the first `HEADER_SIZE` bytes are inspected, so this can be called before the
+    /// header extensions have been received.
+    ///
+    /// # Arguments
+    /// - `bytes`: A byte slice holding at least `HEADER_SIZE` bytes of a serialized header.
+    ///
+    /// # Returns
+    /// The full size of the header, including the checksum, sequence and length extensions.
+    pub fn required_size(bytes: &[u8]) -> usize {
+        let mut size = HEADER_SIZE;
+        if bytes.len() < HEADER_SIZE {
+            return size;
+        }
+        if bytes[5] & CHECKSUM_ALGORITHM_MASK == ChecksumAlgorithm::Crc32 as u8 {
+            size += CHECKSUM_EXTENSION_SIZE;
+        }
+        if bytes[5] & SEQUENCED_FLAG != 0 {
+            size += SEQUENCE_SIZE;
+        }
+        if u16::from_be_bytes([bytes[1], bytes[2]]) == EXTENDED_LENGTH_MARKER {
+            size += EXTENDED_LENGTH_SIZE;
+        }
+        size
+    }
+
+    /// Serializes the header into a byte array.
     ///
-    /// Format: `[type, payload_len (2 bytes), checksum (2 bytes), 0x0A]`.
+    /// Format: `[type, payload_len (2 bytes), checksum (2 bytes), algorithm | flags]`, followed by
+    /// `checksum_low (2 bytes)` for CRC-32, `sequence (4 bytes)` for sequenced packets and
+    /// `payload_len (4 bytes)` for the extended revision, in which case the 2-byte length holds `0xFFFF`.
     ///
     /// # Returns
     /// A boxed array of bytes representing the serialized header.
     pub fn wrap_header(&self) -> Box<[u8]> {
-        let checksum: u16 = self.checksum as u16;
-        let payload_length: u16 = self.payload_length as u16;
+        let checksum = self.checksum.to_be_bytes();
         let header_type: u8 = self.header_type.to_owned() as u8;
+        let end_byte: u8 = self.checksum_algorithm as u8 | self.flags();
+        let payload_length: u16 = match self.payload_length > MAX_LEGACY_PAYLOAD_LENGTH {
+            true => EXTENDED_LENGTH_MARKER,
+            false => self.payload_length as u16,
+        };
 
-        Box::new([
-            header_type,
-            ((payload_length >> 8) & 0xFF) as u8,
-            (payload_length & 0xFF) as u8,
-            ((checksum >> 8) & 0xFF) as u8,
-            (checksum & 0xFF) as u8,
-            0x0A,
-        ])
+        let mut header = Vec::with_capacity(self.size());
+        header.push(header_type);
+        header.extend_from_slice(&payload_length.to_be_bytes());
+        if self.checksum_algorithm == ChecksumAlgorithm::Crc32 {
+            header.extend_from_slice(&checksum[..2]);
+            header.push(end_byte);
+            header.extend_from_slice(&checksum[2..]);
+        } else {
+            header.extend_from_slice(&checksum[2..]);
+            header.push(end_byte);
+        }
+        if let Some(sequence) = self.sequence {
+            header.extend_from_slice(&sequence.to_be_bytes());
+        }
+        if payload_length == EXTENDED_LENGTH_MARKER {
+            header.extend_from_slice(&self.payload_length.to_be_bytes());
+        }
+
+        header.into_boxed_slice()
     }
 
     /// Parses a `PacketHeader` from a byte slice.
     ///
-    /// Validates the format and extracts the header fields.
+    /// Validates the format and extracts the header fields. Accepts the legacy 6-byte header
+    /// as well as headers carrying the CRC-32, sequence and extended length extensions.
     ///
     /// # Arguments
     /// - `bytes`: A byte slice containing the serialized header.
@@ -185,27 +338,129 @@ impl Header {
     /// - `Ok(Header)`: If the byte slice is valid and contains a recognizable header.
     /// - `Err(ProtocolError)`: If the byte slice is invalid or has an unrecognized type.
     pub fn from_bytes(bytes: &[u8]) -> Result<Self, ProtocolError> {
-        if bytes.len() != 6 || bytes[5] != 0x0A {
+        if bytes.len() != Header::required_size(bytes) {
             return Err(ProtocolError::InvalidHeaderError(format!(
                 "Format invalid: {:?}",
                 bytes
             )));
         }
 
+        let checksum_algorithm = ChecksumAlgorithm::try_from(bytes[5] & CHECKSUM_ALGORITHM_MASK)
+            .map_err(|_| {
+                ProtocolError::InvalidHeaderError(format!("Format invalid: {:?}", bytes))
+            })?;
+
         match HeaderType::try_from(bytes[0]) {
             Err(_) => Err(ProtocolError::InvalidHeaderError(
                 "Invalid message type.".to_string(),
             )),
             Ok(header_type) => {
-                let checksum: i16 = u16::from_be_bytes([bytes[3], bytes[4]]) as i16;
-                let payload_length: i16 = u16::from_be_bytes([bytes[1], bytes[2]]) as i16;
+                let mut extensions = &bytes[HEADER_SIZE..];
+                let checksum: u32 = match checksum_algorithm {
+                    ChecksumAlgorithm::Crc32 => {
+                        let checksum =
+                            u32::from_be_bytes([bytes[3], bytes[4], extensions[0], extensions[1]]);
+                        extensions = &extensions[CHECKSUM_EXTENSION_SIZE..];
+                        checksum
+                    }
+                    _ => u16::from_be_bytes([bytes[3], bytes[4]]) as u32,
+                };
+
+                let sequence: Option<u32> = match bytes[5] & SEQUENCED_FLAG {
+                    0 => None,
+                    _ => {
+                        let sequence = u32::from_be_bytes([
+                            extensions[0],
+                            extensions[1],
+                            extensions[2],
+                            extensions[3],
+                        ]);
+                        extensions = &extensions[SEQUENCE_SIZE..];
+                        Some(sequence)
+                    }
+                };
+
+                let payload_length: u32 = match extensions.len() {
+                    EXTENDED_LENGTH_SIZE => u32::from_be_bytes([
+                        extensions[0],
+                        extensions[1],
+                        extensions[2],
+                        extensions[3],
+                    ]),
+                    _ => u16::from_be_bytes([bytes[1], bytes[2]]) as u32,
+                };
 
                 Ok(Self {
                     header_type,
                     payload_length,
                     checksum,
+                    checksum_algorithm,
+                    sequence,
+                    compressed: bytes[5] & COMPRESSED_FLAG != 0,
                 })
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_legacy_header_roundtrip() {
+        let header = Header::new(HeaderType::PlayCard, &[0x01; 300]);
+        let bytes = header.wrap_header();
+        // Small payloads keep the 6-byte header old clients understand
+        assert_eq!(bytes.len(), HEADER_SIZE);
+        assert_eq!(bytes[5], 0x0A);
+        let parsed = Header::from_bytes(&bytes).unwrap();
+        assert_eq!(parsed.payload_length, 300);
+        assert_eq!(parsed.header_type, HeaderType::PlayCard);
+    }
+
+    #[test]
+    fn test_extended_header_roundtrip() {
+        let header = Header::new(HeaderType::GameState, &[0x01; 40_000]);
+        let bytes = header.wrap_header();
+        // Payloads over 32 KiB switch to the extended header
+        assert_eq!(bytes.len(), HEADER_SIZE + EXTENDED_LENGTH_SIZE);
+        assert_eq!(Header::required_size(&bytes[..HEADER_SIZE]), bytes.len());
+        let parsed = Header::from_bytes(&bytes).unwrap();
+        assert_eq!(parsed.payload_length, 40_000);
+        assert_eq!(parsed.checksum, header.checksum);
+    }
+
+    #[test]
+    fn test_crc32_header_roundtrip() {
+        let payload = [0x01; 40_000];
+        let header =
+            Header::with_algorithm(HeaderType::GameState, &payload, ChecksumAlgorithm::Crc32);
+        let bytes = header.wrap_header();
+        // CRC-32 carries the lower checksum half before the extended length
+        assert_eq!(
+            bytes.len(),
+            HEADER_SIZE + CHECKSUM_EXTENSION_SIZE + EXTENDED_LENGTH_SIZE
+        );
+        assert_eq!(Header::required_size(&bytes[..HEADER_SIZE]), bytes.len());
+        let parsed = Header::from_bytes(&bytes).unwrap();
+        assert_eq!(parsed.checksum_algorithm, ChecksumAlgorithm::Crc32);
+        assert_eq!(parsed.checksum, Checksum::crc32(&payload));
+        assert_eq!(parsed.payload_length, 40_000);
+    }
+
+    #[test]
+    fn test_sequenced_header_roundtrip() {
+        let mut header =
+            Header::with_algorithm(HeaderType::GameState, b"state", ChecksumAlgorithm::Crc16);
+        header.sequence = Some(42);
+        let bytes = header.wrap_header();
+        // The sequence flag shares the end byte with the checksum algorithm
+        assert_eq!(bytes[5], ChecksumAlgorithm::Crc16 as u8 | SEQUENCED_FLAG);
+        assert_eq!(bytes.len(), HEADER_SIZE + SEQUENCE_SIZE);
+        assert_eq!(Header::required_size(&bytes[..HEADER_SIZE]), bytes.len());
+        let parsed = Header::from_bytes(&bytes).unwrap();
+        assert_eq!(parsed.sequence, Some(42));
+        assert_eq!(parsed.checksum_algorithm, ChecksumAlgorithm::Crc16);
+    }
+}
@@ -0,0 +1,119 @@
+use crate::models::heartbeat::PongMessage;
+use std::collections::VecDeque;
+
+/// Amount of ping samples kept to compute the rolling statistics.
+const LATENCY_WINDOW: usize = 10;
+
+/// A single round-trip measurement derived from a ping/pong exchange.
+#[derive(Debug, Clone, Copy)]
+struct LatencySample {
+    round_trip: i64,
+    clock_offset: i64,
+}
+
+/// Rolling round-trip time and clock-offset statistics of a client.
+///
+/// Samples are computed NTP-style from the four timestamps of a server ping and the client's pong:
+/// - round trip: `(pong_received_at - ping_sent_at) - (sent_at - received_at)`
+/// - clock offset: `((received_at - ping_sent_at) + (sent_at - pong_received_at)) / 2`
+///
+/// A positive clock offset means the client's clock is ahead of the server's.
+#[derive(Debug, Default)]
+pub struct LatencyStats {
+    samples: VecDeque<LatencySample>,
+}
+
+impl LatencyStats {
+    /// Creates an empty `LatencyStats`.
+    pub fn new() -> Self {
+        Self {
+            samples: VecDeque::with_capacity(LATENCY_WINDOW),
+        }
+    }
+
+    /// Records a sample from a client's answer to a server ping.
+    ///
+    /// # Arguments
+    /// * `pong` - The client's answer, carrying the server ping time and the client's own times.
+    /// * `pong_received_at` - Server time at which the pong was received, in milliseconds.
+    ///
+    /// # Returns
+    /// The round-trip time of this sample, in milliseconds.
+    pub fn record(&mut self, pong: &PongMessage, pong_received_at: i64) -> i64 {
+        let processing = pong.sent_at - pong.received_at;
+        let round_trip = ((pong_received_at - pong.ping_sent_at) - processing).max(0);
+        let clock_offset =
+            ((pong.received_at - pong.ping_sent_at) + (pong.sent_at - pong_received_at)) / 2;
+
+        self.samples.push_back(LatencySample {
+            round_trip,
+            clock_offset,
+        });
+        if self.samples.len() > LATENCY_WINDOW {
+            self.samples.pop_front();
+        }
+
+        round_trip
+    }
+
+    /// Returns the average round-trip time over the window, in milliseconds.
+    pub fn round_trip_ms(&self) -> Option<i64> {
+        if self.samples.is_empty() {
+            return None;
+        }
+
+        let total: i64 = self.samples.iter().map(|s| s.round_trip).sum();
+        Some(total / self.samples.len() as i64)
+    }
+
+    /// Returns the clock offset of the client, in milliseconds.
+    ///
+    /// Uses the sample with the lowest round-trip time, as it is the least skewed by asymmetric delays.
+    pub fn clock_offset_ms(&self) -> Option<i64> {
+        self.samples
+            .iter()
+            .min_by_key(|s| s.round_trip)
+            .map(|s| s.clock_offset)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_latency_empty() {
+        let stats = LatencyStats::new();
+        assert!(stats.round_trip_ms().is_none());
+        assert!(stats.clock_offset_ms().is_none());
+    }
+
+    #[test]
+    fn test_latency_sample() {
+        let mut stats = LatencyStats::new();
+        // 20ms each way, 10ms processing and a client clock 1000ms ahead
+        let pong = PongMessage {
+            ping_sent_at: 0,
+            received_at: 1020,
+            sent_at: 1030,
+        };
+        let round_trip = stats.record(&pong, 50);
+        assert_eq!(round_trip, 40);
+        assert_eq!(stats.round_trip_ms(), Some(40));
+        assert_eq!(stats.clock_offset_ms(), Some(1000));
+    }
+
+    #[test]
+    fn test_latency_rolling_window() {
+        let mut stats = LatencyStats::new();
+        let pong = PongMessage::default();
+        // Old slow samples fall out of the window
+        for _ in 0..LATENCY_WINDOW {
+            stats.record(&pong, 500);
+        }
+        for _ in 0..LATENCY_WINDOW {
+            stats.record(&pong, 100);
+        }
+        assert_eq!(stats.round_trip_ms(), Some(100));
+    }
+}
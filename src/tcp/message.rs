@@ -0,0 +1,173 @@
+use crate::models::client_requests::{
+    AcknowledgeRequest, AttackRequest, ConcedeRequest, ConnectionRequest, EndTurnRequest,
+    MulliganRequest, PlayCardRequest, ReconnectionRequest,
+};
+use crate::models::heartbeat::{PingMessage, PongMessage};
+use crate::models::init_server::InitServerRequest;
+use crate::tcp::header::HeaderType;
+use crate::tcp::packet::Packet;
+use crate::utils::errors::ProtocolError;
+use schemars::{Schema, SchemaGenerator};
+use serde::de::DeserializeOwned;
+
+/// CBOR encoding of an empty map, decoded in place of an empty payload.
+const EMPTY_MAP: [u8; 1] = [0xA0];
+
+/// Declares the messages a client may send, each bound to the `HeaderType` of the same name.
+///
+/// - `control` messages are handled by the protocol itself, with an optional payload type.
+/// - `actions` are game actions, always answered with an `ActionResponse`.
+///
+/// Generates the `Message` enum along with its decoding, header type and schema lookups.
+macro_rules! messages {
+    (
+        control { $($control:ident $(($control_payload:ty))?),* $(,)? }
+        actions { $($action:ident($action_payload:ty)),* $(,)? }
+    ) => {
+        /// A decoded client message, carrying its typed payload.
+        #[derive(Debug)]
+        pub enum Message {
+            $($control $(($control_payload))?,)*
+            $($action($action_payload),)*
+        }
+
+        impl Message {
+            /// Decodes a packet into the message registered for its header type.
+            ///
+            /// # Arguments
+            /// - `packet`: The packet received from the client.
+            ///
+            /// # Returns
+            /// - `Ok(Message)`: If the header type is a client message and its payload is valid.
+            /// - `Err(ProtocolError::InvalidHeaderError)`: If the header is not a client message.
+            /// - `Err(ProtocolError::InvalidPacketPayload)`: If the payload does not match it.
+            pub fn decode(packet: &Packet) -> Result<Self, ProtocolError> {
+                match packet.header.header_type {
+                    $(HeaderType::$control => {
+                        messages!(@decode $control $(($control_payload))?, packet)
+                    })*
+                    $(HeaderType::$action => {
+                        messages!(@decode $action($action_payload), packet)
+                    })*
+                    ref header_type => Err(ProtocolError::InvalidHeaderError(format!(
+                        "`{header_type}` is not a client message"
+                    ))),
+                }
+            }
+
+            /// Returns the header type the message is sent with.
+            pub fn header_type(&self) -> HeaderType {
+                match self {
+                    $(Message::$control { .. } => HeaderType::$control,)*
+                    $(Message::$action { .. } => HeaderType::$action,)*
+                }
+            }
+
+            /// Whether the header type belongs to a game action.
+            pub fn is_action(header_type: &HeaderType) -> bool {
+                matches!(header_type, $(HeaderType::$action)|*)
+            }
+
+            /// Returns the payload schema of every client message, `None` when it has no payload.
+            ///
+            /// # Arguments
+            /// - `generator`: The generator collecting the definitions referenced by the schemas.
+            pub fn payload_schemas(
+                generator: &mut SchemaGenerator,
+            ) -> Vec<(HeaderType, Option<Schema>)> {
+                vec![
+                    $((HeaderType::$control, messages!(@schema generator $(($control_payload))?)),)*
+                    $((HeaderType::$action, messages!(@schema generator ($action_payload))),)*
+                ]
+            }
+        }
+    };
+
+    (@decode $variant:ident, $packet:ident) => {
+        Ok(Message::$variant)
+    };
+
+    (@decode $variant:ident($payload:ty), $packet:ident) => {
+        decode_payload::<$payload>(&$packet.payload).map(Message::$variant)
+    };
+
+    (@schema $generator:ident) => {
+        None
+    };
+
+    (@schema $generator:ident ($payload:ty)) => {
+        Some($generator.subschema_for::<$payload>())
+    };
+}
+
+messages! {
+    control {
+        Disconnect,
+        Connect(ConnectionRequest),
+        Reconnect(ReconnectionRequest),
+        Ack(AcknowledgeRequest),
+        Ping(PingMessage),
+        Pong(PongMessage),
+        InitServer(InitServerRequest),
+    }
+    actions {
+        PlayCard(PlayCardRequest),
+        AttackPlayer(AttackRequest),
+        EndTurn(EndTurnRequest),
+        Mulligan(MulliganRequest),
+        Concede(ConcedeRequest),
+    }
+}
+
+/// Deserializes a CBOR payload into its message type.
+///
+/// An empty payload is read as an empty map, so messages whose fields all have defaults may
+/// be sent without one.
+fn decode_payload<T: DeserializeOwned>(payload: &[u8]) -> Result<T, ProtocolError> {
+    let payload = match payload.is_empty() {
+        true => &EMPTY_MAP[..],
+        false => payload,
+    };
+    serde_cbor::from_slice(payload)
+        .map_err(|error| ProtocolError::InvalidPacketPayload(error.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_typed_message() {
+        let request = AcknowledgeRequest { sequence: 42 };
+        let packet = Packet::new(HeaderType::Ack, &serde_cbor::to_vec(&request).unwrap());
+
+        match Message::decode(&packet) {
+            Ok(Message::Ack(request)) => assert_eq!(request.sequence, 42),
+            _ => panic!("Expected an acknowledgement"),
+        }
+    }
+
+    #[test]
+    fn test_decode_empty_payload() {
+        // Pings may omit their payload, acknowledgements may not
+        let ping = Packet::new(HeaderType::Ping, b"");
+        assert!(matches!(Message::decode(&ping), Ok(Message::Ping(_))));
+
+        let ack = Packet::new(HeaderType::Ack, b"");
+        assert!(matches!(
+            Message::decode(&ack),
+            Err(ProtocolError::InvalidPacketPayload(_))
+        ));
+    }
+
+    #[test]
+    fn test_decode_rejects_server_messages() {
+        let packet = Packet::new(HeaderType::GameState, b"");
+        assert!(matches!(
+            Message::decode(&packet),
+            Err(ProtocolError::InvalidHeaderError(_))
+        ));
+        assert!(Message::is_action(&HeaderType::PlayCard));
+        assert!(!Message::is_action(&HeaderType::Ping));
+    }
+}
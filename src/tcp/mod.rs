@@ -2,4 +2,13 @@ pub mod client;
 pub mod protocol;
 pub mod server;
 pub mod header;
+pub mod decoder;
+pub mod handshake;
+pub mod message;
+pub mod latency;
+pub mod outbound;
+pub mod schema;
+pub mod tls;
+pub mod transport;
+pub mod websocket;
 mod packet;
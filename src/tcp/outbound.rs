@@ -0,0 +1,162 @@
+use crate::tcp::packet::Packet;
+use crate::utils::errors::NetworkError;
+use tokio::sync::{mpsc, watch};
+
+/// A packet waiting to be written by a client's writer task.
+///
+/// The variant decides how the packet is treated when the client falls behind:
+/// - `Control` - Handshake answers, pings and pongs. Never dropped and never sequenced.
+/// - `Response` - Answers to client actions. Never dropped; an overflow disconnects the client.
+/// - `GameState` - Broadcast game states. Only the latest one is kept, stale ones are dropped.
+/// - `Closing` - A last control packet, after which the client is disconnected.
+/// - `ResendMissed` - Resends the packets the client has not acknowledged yet.
+pub enum Outbound {
+    Control(Packet),
+    Closing(Packet),
+    Response(Packet),
+    GameState(Packet),
+    ResendMissed,
+}
+
+/// The sending side of a client's outbound queue.
+///
+/// Control packets and responses go through a bounded channel, while game states overwrite a
+/// single slot so a slow client only ever receives the most recent one.
+pub struct OutboundQueue {
+    queue: mpsc::Sender<Outbound>,
+    game_state: watch::Sender<Option<Packet>>,
+}
+
+/// The receiving side of a client's outbound queue, owned by the writer task.
+pub struct OutboundReceiver {
+    queue: mpsc::Receiver<Outbound>,
+    game_state: watch::Receiver<Option<Packet>>,
+}
+
+impl OutboundQueue {
+    /// Creates a new outbound queue.
+    ///
+    /// # Arguments
+    /// - `capacity`: The amount of control packets and responses that may wait to be written.
+    ///
+    /// # Returns
+    /// The queue and the receiver to hand to the writer task.
+    pub fn new(capacity: usize) -> (Self, OutboundReceiver) {
+        let (queue_tx, queue_rx) = mpsc::channel(capacity.max(1));
+        let (state_tx, state_rx) = watch::channel(None);
+        (
+            Self {
+                queue: queue_tx,
+                game_state: state_tx,
+            },
+            OutboundReceiver {
+                queue: queue_rx,
+                game_state: state_rx,
+            },
+        )
+    }
+
+    /// Pushes a packet to the queue without waiting.
+    ///
+    /// Game states replace the pending one instead of taking a slot in the queue.
+    ///
+    /// # Returns
+    /// - `Ok(())`: If the packet was queued.
+    /// - `Err(NetworkError::OutboundQueueFull)`: If the client is not keeping up with its packets.
+    /// - `Err(NetworkError::PackageWriteError)`: If the writer task is gone.
+    pub fn push(&self, outbound: Outbound) -> Result<(), NetworkError> {
+        if let Outbound::GameState(packet) = outbound {
+            self.game_state.send_replace(Some(packet));
+            return Ok(());
+        }
+
+        self.queue.try_send(outbound).map_err(|error| match error {
+            mpsc::error::TrySendError::Full(_) => NetworkError::OutboundQueueFull,
+            mpsc::error::TrySendError::Closed(_) => {
+                NetworkError::PackageWriteError("Writer task is closed".to_string())
+            }
+        })
+    }
+}
+
+impl OutboundReceiver {
+    /// Waits for the next packet to write.
+    ///
+    /// Queued control packets and responses are always written before the pending game state.
+    ///
+    /// # Returns
+    /// - `Some(Outbound)`: The next packet to write.
+    /// - `None`: If the client's queue has been dropped.
+    pub async fn recv(&mut self) -> Option<Outbound> {
+        loop {
+            tokio::select! {
+                biased;
+                outbound = self.queue.recv() => return outbound,
+                changed = self.game_state.changed() => {
+                    changed.ok()?;
+                    if let Some(packet) = self.game_state.borrow_and_update().clone() {
+                        return Some(Outbound::GameState(packet));
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tcp::header::HeaderType;
+
+    #[tokio::test]
+    async fn test_outbound_drops_stale_game_state() {
+        let (queue, mut receiver) = OutboundQueue::new(4);
+        queue
+            .push(Outbound::GameState(Packet::new(
+                HeaderType::GameState,
+                b"old",
+            )))
+            .unwrap();
+        queue
+            .push(Outbound::GameState(Packet::new(
+                HeaderType::GameState,
+                b"new",
+            )))
+            .unwrap();
+
+        // Only the latest game state reaches the writer
+        match receiver.recv().await {
+            Some(Outbound::GameState(packet)) => assert_eq!(&*packet.payload, b"new"),
+            _ => panic!("Expected a game state"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_outbound_responses_before_game_state() {
+        let (queue, mut receiver) = OutboundQueue::new(4);
+        queue
+            .push(Outbound::GameState(Packet::new(HeaderType::GameState, b"")))
+            .unwrap();
+        queue
+            .push(Outbound::Response(Packet::new(HeaderType::PlayCard, b"")))
+            .unwrap();
+
+        assert!(matches!(receiver.recv().await, Some(Outbound::Response(_))));
+        assert!(matches!(
+            receiver.recv().await,
+            Some(Outbound::GameState(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_outbound_overflow() {
+        let (queue, _receiver) = OutboundQueue::new(1);
+        queue
+            .push(Outbound::Response(Packet::new(HeaderType::PlayCard, b"")))
+            .unwrap();
+
+        // Responses are never dropped, so a full queue is reported to the caller
+        let overflow = queue.push(Outbound::Response(Packet::new(HeaderType::PlayCard, b"")));
+        assert!(matches!(overflow, Err(NetworkError::OutboundQueueFull)));
+    }
+}
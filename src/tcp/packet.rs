@@ -1,5 +1,8 @@
 use crate::logger;
-use crate::tcp::header::{Header, HeaderType};
+use crate::models::error_response::{ErrorCode, ErrorEnvelope};
+use crate::tcp::handshake::Capabilities;
+use crate::tcp::header::{Header, HeaderType, HEADER_SIZE, MAX_PAYLOAD_LENGTH};
+use crate::utils::compression::{self, COMPRESSION_THRESHOLD};
 use crate::utils::errors::ProtocolError;
 use crate::utils::logger::Logger;
 
@@ -17,7 +20,9 @@ pub struct Packet {
 impl Packet {
     /// Parses a raw byte slice into a `Packet`.
     ///
-    /// Expects a 5-byte header followed by the payload (skips byte 5: delimiter).
+    /// Expects a 6-byte (or 10-byte extended) header followed by the payload.
+    /// Compressed payloads are decompressed, so the packet always holds the original payload
+    /// and its header the original payload length.
     ///
     /// # Arguments
     /// - `protocol`: A byte slice containing the serialized packet data.
@@ -26,15 +31,27 @@ impl Packet {
     /// - `Ok(Packet)`: If the byte slice is valid and contains a recognizable packet.
     /// - `Err(ProtocolError)`: If the byte slice is invalid or the header cannot be parsed.
     pub fn parse(protocol: &[u8]) -> Result<Self, ProtocolError> {
-        if protocol.len() < 6 {
+        if protocol.len() < HEADER_SIZE {
             logger!(ERROR, "[PROTOCOL] Not enough bytes for a valid packet");
             return Err(ProtocolError::InvalidPacketError(
                 "Not enough bytes for a valid packet".to_string(),
             ));
         }
 
-        let header = Header::from_bytes(&protocol[..6])?;
-        let payload = protocol[6..].to_owned().into_boxed_slice();
+        let header_size = Header::required_size(protocol);
+        if protocol.len() < header_size {
+            logger!(ERROR, "[PROTOCOL] Not enough bytes for a valid packet");
+            return Err(ProtocolError::InvalidPacketError(
+                "Not enough bytes for a valid packet".to_string(),
+            ));
+        }
+
+        let mut header = Header::from_bytes(&protocol[..header_size])?;
+        let mut payload = protocol[header_size..].to_owned().into_boxed_slice();
+        if header.compressed {
+            payload = compression::decompress(&payload, MAX_PAYLOAD_LENGTH)?.into_boxed_slice();
+            header.payload_length = payload.len() as u32;
+        }
         Ok(Self { header, payload })
     }
 
@@ -54,6 +71,19 @@ impl Packet {
         Self { header, payload }
     }
 
+    /// Creates an error packet whose payload is the `ErrorEnvelope` of the given error.
+    ///
+    /// # Arguments
+    /// - `header_type`: The type of the message (e.g., `InvalidChecksum`, `PlayCard`).
+    /// - `error`: The error to report to the client.
+    ///
+    /// # Returns
+    /// A new `Packet` carrying the CBOR-encoded envelope.
+    pub fn error(header_type: HeaderType, error: &impl ErrorCode) -> Self {
+        let payload = serde_cbor::to_vec(&ErrorEnvelope::from(error)).unwrap_or_default();
+        Packet::new(header_type, &payload)
+    }
+
     /// Serializes the packet into a byte slice.
     ///
     /// Combines the header and payload into a single buffer for transmission.
@@ -69,4 +99,68 @@ impl Packet {
 
         packet.into_boxed_slice()
     }
+
+    /// Serializes the packet using the capabilities negotiated for a connection.
+    ///
+    /// Recomputes the header so that packets shared between connections (e.g. broadcasts)
+    /// match the integrity mode selected by each client. The sequence id is only written
+    /// for connections that negotiated sequencing, and payloads of at least
+    /// `COMPRESSION_THRESHOLD` bytes are compressed for connections that negotiated compression.
+    /// The checksum always covers the uncompressed payload.
+    ///
+    /// # Arguments
+    /// - `capabilities`: The capabilities of the receiving connection.
+    ///
+    /// # Returns
+    /// A boxed array of bytes representing the serialized packet.
+    pub fn wrap_packet_with(&self, capabilities: &Capabilities) -> Box<[u8]> {
+        let mut header = Header::with_algorithm(
+            self.header.header_type.clone(),
+            &self.payload,
+            capabilities.checksum_algorithm,
+        );
+        if capabilities.sequencing {
+            header.sequence = self.header.sequence;
+        }
+
+        let mut payload = self.payload.clone();
+        if capabilities.compression && payload.len() >= COMPRESSION_THRESHOLD {
+            if let Some(compressed) = compression::compress(&payload) {
+                header.compressed = true;
+                header.payload_length = compressed.len() as u32;
+                payload = compressed.into_boxed_slice();
+            }
+        }
+
+        Packet { header, payload }.wrap_packet()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::checksum::{Checksum, ChecksumAlgorithm};
+
+    #[test]
+    fn test_compressed_packet_roundtrip() {
+        let capabilities = Capabilities {
+            compression: true,
+            checksum_algorithm: ChecksumAlgorithm::Crc32,
+            ..Capabilities::default()
+        };
+        let payload = vec![0x2A; 8192];
+        let bytes = Packet::new(HeaderType::GameState, &payload).wrap_packet_with(&capabilities);
+        assert!(bytes.len() < payload.len());
+
+        // Parsing hands back the original payload, still matching its checksum
+        let parsed = Packet::parse(&bytes).unwrap();
+        assert!(parsed.header.compressed);
+        assert_eq!(parsed.header.payload_length, 8192);
+        assert_eq!(&*parsed.payload, &payload[..]);
+        assert!(Checksum::verify(
+            &ChecksumAlgorithm::Crc32,
+            parsed.header.checksum,
+            &parsed.payload
+        ));
+    }
 }
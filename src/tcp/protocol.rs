@@ -1,101 +1,250 @@
 use super::client::{Client, TemporaryClient};
 use crate::game::entity::player::{Player, PlayerView};
 use crate::game::game::GameInstance;
-use crate::models::client_requests::PlayCardRequest;
-use crate::models::exit_code::ExitCode;
+use crate::models::action_response::ActionResponse;
+use crate::models::error_response::ErrorEnvelope;
+use crate::models::client_requests::{
+    AcknowledgeRequest, AttackRequest, ConcedeRequest, ConnectionRequest, EndTurnRequest,
+    MulliganRequest, PlayCardRequest, ReconnectionRequest,
+};
+use crate::models::exit_code::{ExitCode, ExitStatus};
+use crate::models::heartbeat::{PingMessage, PongMessage};
+use crate::tcp::handshake::Capabilities;
 use crate::tcp::header::HeaderType;
 use crate::tcp::header::HeaderType::PlayCard;
+use crate::tcp::message::Message;
+use crate::tcp::outbound::Outbound;
 use crate::tcp::packet::Packet;
 use crate::tcp::server::ServerInstance;
-use crate::utils::errors::{NetworkError, PlayerConnectionError};
+use crate::tcp::transport::{self, TransportWriter};
+use crate::utils::errors::{NetworkError, PlayerConnectionError, ProtocolError};
 use crate::{
     logger,
     utils::{checksum::Checksum, logger::Logger},
+    SETTINGS,
 };
+use chrono::Utc;
+use std::net::SocketAddr;
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::io::AsyncWriteExt;
-use tokio::sync::broadcast::Sender;
-use tokio::sync::{broadcast, Mutex, RwLock};
+use tokio::sync::RwLock;
 
 /// The Protocol struct handles the communication protocol for the server, managing client connections and packet processing.
 pub struct Protocol {
     pub game_instance: Arc<GameInstance>,
     pub server_instance: Arc<ServerInstance>,
-    pub transmitter: Arc<Mutex<Sender<Packet>>>, // The transmitter for broadcasting packets to clients.
 }
 
 impl Protocol {
     pub fn new(server_instance: Arc<ServerInstance>, game_instance: Arc<GameInstance>) -> Self {
-        let (tx, _) = broadcast::channel::<Packet>(10);
         Protocol {
             game_instance,
             server_instance,
-            transmitter: Arc::new(Mutex::new(tx)),
         }
     }
 
+    /// Creates the protocol of a test match between `red` and `blue`, initializing the test
+    /// settings.
+    #[cfg(test)]
+    pub async fn for_tests() -> Self {
+        crate::models::settings::Settings::init_for_tests().await;
+        let game_instance = Arc::new(GameInstance::for_tests());
+        let server_instance = ServerInstance::for_tests(Arc::clone(&game_instance)).await;
+        Protocol::new(Arc::new(server_instance), game_instance)
+    }
+
     /// Handles incoming packets from a client.
     ///
-    /// - Parses the packet from the provided buffer.
-    /// - Validates the packet's checksum.
+    /// - Receives a packet already reassembled by the client's `PacketDecoder`.
+    /// - Validates the packet's checksum with the algorithm selected for the connection.
     /// - Logs the packet details.
     /// - If the packet is valid, it calls `handle_packet` to process it.
     /// - If the checksum is invalid, it sends an `InvalidChecksum` packet to the client and disconnects.
     ///
     /// # Arguments
     /// * `client` - The client that sent the packet.
-    /// * `buffer` - The byte buffer containing the incoming packet data.
+    /// * `packet` - The complete packet decoded from the client's stream.
     ///
     /// # Returns
     /// * None if the packet is processed successfully.
-    /// * Sends an `InvalidChecksum` packet and disconnects the client if the checksum is invalid.
+    /// * Sends an `InvalidChecksum` packet and disconnects the client if the checksum is invalid
+    ///   or was computed with a different algorithm than the one selected for the connection.
+    /// * Sends an `InvalidPacketPayload` packet if the payload is compressed but compression was not negotiated.
     ///
     /// Log all outcomes, including errors and successful packet processing.
-    pub async fn handle_incoming(&self, client: Arc<Client>, buffer: &[u8]) {
-        match Packet::parse(&buffer) {
-            Err(error) => logger!(ERROR, "{}", error.to_string()),
-            Ok(packet) => {
-                logger!(
-                    DEBUG,
-                    "[PROTOCOL] Received packet: {{ type: {}, size: {} }}",
-                    packet.header.header_type.to_string(),
-                    packet.header.payload_length
-                );
-
-                if !Checksum::check(&packet.header.checksum, &packet.payload) {
-                    logger!(WARN, "[PROTOCOL] Invalid checksum value");
-                    let packet = Packet::new(HeaderType::InvalidChecksum, b"");
-                    self.send_or_disconnect(client, &packet).await;
-                    return;
-                }
-                self.handle_packet(client, &packet).await
-            }
+    pub async fn handle_incoming(&self, client: Arc<Client>, packet: Packet) {
+        logger!(
+            DEBUG,
+            "[PROTOCOL] Received packet: {{ type: {}, size: {} }}",
+            packet.header.header_type.to_string(),
+            packet.header.payload_length
+        );
+
+        let checksum_algorithm = client.capabilities.read().await.checksum_algorithm;
+        if packet.header.checksum_algorithm != checksum_algorithm
+            || !Checksum::verify(&checksum_algorithm, packet.header.checksum, &packet.payload)
+        {
+            logger!(WARN, "[PROTOCOL] Invalid checksum value");
+            let packet =
+                Packet::error(HeaderType::InvalidChecksum, &ProtocolError::InvalidChecksum);
+            self.send_or_disconnect(client, &packet).await;
+            return;
         }
+
+        if packet.header.compressed && !client.capabilities.read().await.compression {
+            logger!(
+                WARN,
+                "[PROTOCOL] Compressed packet without negotiated compression"
+            );
+            let packet = Packet::error(
+                HeaderType::InvalidPacketPayload,
+                &ProtocolError::CompressionNotNegotiated,
+            );
+            self.send_or_disconnect(client, &packet).await;
+            return;
+        }
+        self.handle_packet(client, &packet).await
     }
 
-    /// Sends a packet to the client, retrying up to 3 times if the sending fails.
+    /// Pushes a response packet to the client's outbound queue.
     ///
-    /// If all attempts fail, it disconnects the client and returns an error.
+    /// Responses are never dropped: if the client's queue is full, the client is disconnected.
     ///
     /// # Arguments
     /// * `client` - The client to which the packet should be sent.
     /// * `packet` - The packet to send.
     ///
     /// # Returns
-    /// * `Ok(())` if the packet was sent successfully.
-    /// * `Err(NetworkError)` if the packet could not be sent after 3 attempts.
+    /// * `Ok(())` if the packet was queued.
+    /// * `Err(NetworkError)` if the packet is too large or the client's queue is full.
     pub async fn send_packet(
         &self,
         client: Arc<Client>,
         packet: &Packet,
     ) -> Result<(), NetworkError> {
+        self.queue_outbound(client, Outbound::Response(packet.clone()))
+            .await
+    }
+
+    /// Pushes a packet to the client's outbound queue, to be written by its writer task.
+    ///
+    /// Packets whose payload exceeds the negotiated maximum payload length are refused.
+    /// If the queue overflows, the client is disconnected.
+    ///
+    /// # Arguments
+    /// * `client` - The client to which the packet should be sent.
+    /// * `outbound` - The packet to send, along with how to treat it when the client falls behind.
+    ///
+    /// # Returns
+    /// * `Ok(())` if the packet was queued.
+    /// * `Err(NetworkError)` if the packet is too large or the client's queue is full.
+    pub async fn queue_outbound(
+        &self,
+        client: Arc<Client>,
+        outbound: Outbound,
+    ) -> Result<(), NetworkError> {
+        let max_payload_length = client.capabilities.read().await.max_payload_length;
+        if let Outbound::Control(packet)
+        | Outbound::Closing(packet)
+        | Outbound::Response(packet)
+        | Outbound::GameState(packet) = &outbound
+        {
+            if packet.header.payload_length > max_payload_length {
+                return Err(NetworkError::PayloadTooLarge(packet.header.payload_length));
+            }
+        }
+
+        let result = client.outbound.push(outbound);
+        if let Err(NetworkError::OutboundQueueFull) = result {
+            let addr = *client.addr.read().await;
+            logger!(WARN, "[PROTOCOL] Outbound queue of `{addr}` overflowed");
+            if *client.connected.read().await {
+                self.disconnect(client).await;
+            }
+        }
+
+        result
+    }
+
+    /// Writes a packet taken from the client's outbound queue. Only called by the client's writer task.
+    ///
+    /// - Control packets are written unsequenced, and dropped while the client is disconnected.
+    /// - Closing packets are written unsequenced, then the client is disconnected.
+    /// - Responses and game states are given the next sequence id of the connection. When
+    ///   sequencing was negotiated, or while the client is disconnected, they are also kept until
+    ///   the client acknowledges them or reconnects.
+    /// - If the write fails, the client is disconnected.
+    ///
+    /// # Arguments
+    /// * `client` - The client whose packet is written.
+    /// * `outbound` - The packet taken from the queue.
+    pub async fn write_outbound(&self, client: Arc<Client>, outbound: Outbound) {
+        let capabilities = client.capabilities.read().await.clone();
+        let connected = *client.connected.read().await;
+
+        let (packet, closing) = match outbound {
+            Outbound::ResendMissed => {
+                self.resend_missed_packets(client, &capabilities).await;
+                return;
+            }
+            Outbound::Control(packet) => (packet, false),
+            Outbound::Closing(packet) => (packet, true),
+            Outbound::Response(packet) | Outbound::GameState(packet) => {
+                let packet = client.sequence_packet(&packet).await;
+                if capabilities.sequencing || !connected {
+                    client.queue_packet(packet.clone()).await;
+                }
+                if !connected {
+                    let addr = client.addr.read().await;
+                    logger!(
+                        WARN,
+                        "[PROTOCOL] `{addr}` has {} packets in queue",
+                        client.missed_packets.read().await.len()
+                    );
+                }
+                (packet, false)
+            }
+        };
+
+        if !connected {
+            return;
+        }
+
+        let mut stream_guard = client.write_stream.write().await;
+        let addr = *client.addr.read().await;
+        let result = self
+            .write_packet(&mut stream_guard, &addr, &packet, &capabilities)
+            .await;
+        drop(stream_guard);
+
+        if (result.is_err() || closing) && *client.connected.read().await {
+            self.disconnect(client).await;
+        }
+    }
+
+    /// Writes a packet to the given stream, retrying up to 3 times if the writing fails.
+    ///
+    /// # Arguments
+    /// * `stream` - The write half of the client's connection.
+    /// * `addr` - The client's address, used for logging.
+    /// * `packet` - The packet to write.
+    /// * `capabilities` - The capabilities negotiated for the connection.
+    ///
+    /// # Returns
+    /// * `Ok(())` if the packet was written successfully.
+    /// * `Err(NetworkError)` if the packet could not be written after 3 attempts.
+    async fn write_packet(
+        &self,
+        stream: &mut TransportWriter,
+        addr: &SocketAddr,
+        packet: &Packet,
+        capabilities: &Capabilities,
+    ) -> Result<(), NetworkError> {
+        let packet_data = packet.wrap_packet_with(capabilities);
         let mut tries = 0;
         while tries < 3 {
-            let addr = client.addr.read().await;
-            let packet_data = packet.wrap_packet();
-            let mut stream_guard = client.write_stream.write().await;
-            if stream_guard.write_all(&packet_data).await.is_err() {
+            if stream.write_all(&packet_data).await.is_err() || stream.flush().await.is_err() {
                 tokio::time::sleep(Duration::from_millis(500)).await;
                 tries += 1;
                 continue;
@@ -113,6 +262,19 @@ impl Protocol {
         Err(NetworkError::PackageWriteError("Unknown error".to_string()))
     }
 
+    /// Sends a control packet (handshake answers, pings and pongs) to the client.
+    ///
+    /// Control packets are not sequenced, so they are never queued for a resend.
+    ///
+    /// # Arguments
+    /// * `client` - The client to which the packet should be sent.
+    /// * `packet` - The packet to send.
+    async fn send_control_packet(&self, client: Arc<Client>, packet: &Packet) {
+        let _ = self
+            .queue_outbound(client, Outbound::Control(packet.clone()))
+            .await;
+    }
+
     /// Disconnects a client by setting its connected state to false and logging the disconnection.
     ///
     /// # Arguments
@@ -121,7 +283,7 @@ impl Protocol {
     /// This function updates the client's connection status and logs the disconnection event.
     ///
     /// It does not send any packets to the client; it simply marks the client as disconnected.
-    async fn disconnect(&self, client: Arc<Client>) {
+    pub async fn disconnect(&self, client: Arc<Client>) {
         let addr = client.addr.read().await;
         logger!(INFO, "[PROTOCOL] Client `{addr}` disconnected");
         let mut connected_guard = client.connected.write().await;
@@ -142,37 +304,98 @@ impl Protocol {
 
     /// Sends a packet to the client and then disconnects the client independent of the result.
     ///
+    /// The client is disconnected by its writer task once the packet has been written.
+    ///
     /// # Arguments
     /// * `client` - The client to which the packet should be sent.
     /// * `packet` - The packet to send.
     async fn send_and_disconnect(&self, client: Arc<Client>, packet: &Packet) {
         let client_clone = Arc::clone(&client);
-        let _ = self.send_packet(client, packet).await;
-        self.disconnect(client_clone).await;
+        if self
+            .queue_outbound(client, Outbound::Closing(packet.clone()))
+            .await
+            .is_err()
+            && *client_clone.connected.read().await
+        {
+            self.disconnect(client_clone).await;
+        }
     }
 
-    /// Handles a packet received from a client based on its header type.
+    /// Handles a packet received from a client based on the message it decodes to.
+    ///
+    /// Messages that are only valid before authentication are rejected as invalid headers.
     async fn handle_packet(&self, client: Arc<Client>, packet: &Packet) {
-        let message_type = &packet.header.header_type;
-        match message_type {
-            HeaderType::Disconnect => self.handle_disconnect(client).await,
-            HeaderType::PlayCard => self.handle_play_card(client, &packet).await,
-            _ => {
-                logger!(WARN, "[PROTOCOL] Invalid header");
-                let packet = Packet::new(HeaderType::InvalidHeader, b"");
-                self.send_or_disconnect(client, &packet).await;
+        let message = match Message::decode(packet) {
+            Ok(message) => message,
+            Err(error) => return self.reject_message(client, packet, &error).await,
+        };
+
+        match message {
+            Message::Disconnect => self.handle_disconnect(client).await,
+            Message::Ack(request) => self.handle_ack(client, &request).await,
+            Message::Ping(ping) => self.handle_ping(client, &ping).await,
+            Message::Pong(pong) => self.handle_pong(client, &pong).await,
+            Message::PlayCard(request) => self.handle_play_card(client, &request).await,
+            Message::AttackPlayer(request) => self.handle_attack(client, &request).await,
+            Message::EndTurn(request) => self.handle_end_turn(client, &request).await,
+            Message::Mulligan(request) => self.handle_mulligan(client, &request).await,
+            Message::Concede(request) => self.handle_concede(client, &request).await,
+            message => {
+                let error = ProtocolError::InvalidHeaderError(format!(
+                    "`{}` is not expected from a connected client",
+                    message.header_type()
+                ));
+                self.reject_message(client, packet, &error).await;
             }
         }
+
+        // Any game action may have ended the match.
+        if Message::is_action(&packet.header.header_type) {
+            self.check_match_end().await;
+        }
+    }
+
+    /// Answers a packet that could not be handled.
+    ///
+    /// Game actions are answered with a rejected `ActionResponse` echoing their request id, any
+    /// other packet with an `InvalidHeader` or `InvalidPacketPayload` error.
+    ///
+    /// # Arguments
+    /// * `client` - The client that sent the packet.
+    /// * `packet` - The packet being rejected.
+    /// * `error` - Why the packet was rejected.
+    async fn reject_message(&self, client: Arc<Client>, packet: &Packet, error: &ProtocolError) {
+        let header_type = packet.header.header_type.clone();
+        logger!(WARN, "[PROTOCOL] Rejected `{header_type}` packet ({error})");
+
+        if Message::is_action(&header_type) {
+            let response = ActionResponse::rejected(
+                ActionResponse::request_id_of(&packet.payload),
+                ErrorEnvelope::from(error),
+            );
+            return self.send_action_response(client, header_type, &response).await;
+        }
+
+        let error_type = match error {
+            ProtocolError::InvalidPacketPayload(_) => HeaderType::InvalidPacketPayload,
+            _ => HeaderType::InvalidHeader,
+        };
+        let packet = Packet::error(error_type, error);
+        self.send_or_disconnect(client, &packet).await;
     }
 
     /// Handles a new connection request from a temporary client.
     ///
-    /// This function authenticates the player based on the provided packet payload.
-    /// If the authentication is successful, it creates a new `Client` instance and adds it to the server's player list.
+    /// This function negotiates the protocol version and capabilities, then authenticates the player
+    /// based on the provided packet payload. Unsupported versions are rejected with an
+    /// `UnsupportedProtocolVersion` packet before contacting the authentication server.
+    /// If the authentication is successful, it creates a new `Client` instance and adds it to the server's player list,
+    /// answering versioned clients with the agreed `Capabilities`.
+    /// Authentication failures are answered with an `ERROR` packet before closing the connection.
     /// If the temporary client cannot be unwrapped, it returns an error.
     /// # Arguments
     /// * `temp_client` - The temporary client that is attempting to connect.
-    /// * `packet` - The packet containing the authentication payload.
+    /// * `request` - The authentication payload.
     ///
     /// # Returns
     /// * `Ok(())` if the connection is successfully established.
@@ -180,9 +403,25 @@ impl Protocol {
     pub async fn handle_connect(
         self: Arc<Self>,
         temp_client: Arc<TemporaryClient>,
-        packet: &Packet,
+        request: ConnectionRequest,
     ) -> Result<(), PlayerConnectionError> {
-        let player_authentication = Player::new_connection(&packet.payload).await?;
+        let capabilities = match Capabilities::negotiate(
+            request.protocol_version,
+            &request.capabilities,
+            temp_client.checksum_algorithm,
+        ) {
+            Some(capabilities) => capabilities,
+            None => {
+                return Err(self
+                    .reject_version(temp_client, request.protocol_version)
+                    .await)
+            }
+        };
+
+        let player_authentication = match Player::new_connection(&request).await {
+            Ok(player_authentication) => player_authentication,
+            Err(error) => return Err(self.reject_temp_client(temp_client, error).await),
+        };
         logger!(
             INFO,
             "[PROTOCOL] Client `{}` has been authenticated as player `{}`.",
@@ -200,17 +439,30 @@ impl Protocol {
         if let Some(connected_player) = connected_players.get(&player_authentication.player_id) {
             match Arc::try_unwrap(temp_client) {
                 Ok(temp) => {
-                    let (read, write) = temp.stream.into_split();
+                    let (read, write) = transport::split(temp.stream);
                     let client = Arc::new(Client::new(
                         read,
                         write,
                         temp.addr,
+                        temp.decoder,
+                        capabilities.clone(),
                         self.clone(),
                         connected_player.clone(),
                     ));
                     let mut clients_guard = self.server_instance.connected_clients.write().await;
                     clients_guard.insert(player_authentication.player_id, client.clone());
 
+                    // The match starts once every player has connected for the first time.
+                    if clients_guard.len() == connected_players.len() {
+                        let game_instance = Arc::clone(&self.game_instance);
+                        tokio::spawn(async move { game_instance.start_game().await });
+                    }
+
+                    if request.protocol_version.is_some() {
+                        self.send_capabilities(client.clone(), HeaderType::Connect, &capabilities)
+                            .await;
+                    }
+
                     tokio::spawn({
                         async move {
                             client.clone().connect().await;
@@ -224,20 +476,25 @@ impl Protocol {
                 )),
             }
         } else {
-            Err(PlayerConnectionError::PlayerNotConnected)
+            let error = PlayerConnectionError::PlayerNotConnected;
+            Err(self.reject_temp_client(temp_client, error).await)
         }
     }
 
     /// Handles a reconnection request from a temporary client.
     ///
-    /// This function attempts to authenticate the player based on the provided packet payload.
-    /// If the player is found in the server's player list, it attempts to reconnect the player.
+    /// This function negotiates the protocol version and capabilities, then attempts to authenticate
+    /// the player based on the provided packet payload. Unsupported versions are rejected with an
+    /// `UnsupportedProtocolVersion` packet.
+    /// If the player is found in the server's player list, it attempts to reconnect the player and resends
+    /// every queued packet newer than the last sequence id the client reports having received.
     /// If the temporary client cannot be unwrapped, it returns an error.
     /// If the player is not found, it returns an error indicating that the player is not connected to the match.
+    /// Authentication failures are answered with an `ERROR` packet before closing the connection.
     ///
     /// # Arguments
     /// * `temp_client` - The temporary client that is attempting to reconnect.
-    /// * `packet` - The packet containing the authentication payload.
+    /// * `request` - The authentication payload.
     ///
     /// # Returns
     /// * `Ok(())` if the reconnection is successfully established.
@@ -245,7 +502,7 @@ impl Protocol {
     pub async fn handle_reconnect(
         self: Arc<Self>,
         temp_client: Arc<TemporaryClient>,
-        packet: &Packet,
+        request: ReconnectionRequest,
     ) -> Result<(), PlayerConnectionError> {
         logger!(
             INFO,
@@ -253,7 +510,23 @@ impl Protocol {
             &temp_client.addr
         );
 
-        let authenticated_player = Player::reconnection(&packet.payload).await?;
+        let capabilities = match Capabilities::negotiate(
+            request.protocol_version,
+            &request.capabilities,
+            temp_client.checksum_algorithm,
+        ) {
+            Some(capabilities) => capabilities,
+            None => {
+                return Err(self
+                    .reject_version(temp_client, request.protocol_version)
+                    .await)
+            }
+        };
+
+        let authenticated_player = match Player::reconnection(&request).await {
+            Ok(authenticated_player) => authenticated_player,
+            Err(error) => return Err(self.reject_temp_client(temp_client, error).await),
+        };
         logger!(
             INFO,
             "[PROTOCOL] Client `{}` has been authenticated as player `{}`.",
@@ -276,13 +549,115 @@ impl Protocol {
                     );
 
                     let client_clone = Arc::clone(&client);
-                    client_clone.reconnect(temp).await;
+                    client_clone.reconnect(temp, capabilities.clone()).await;
+
+                    if request.protocol_version.is_some() {
+                        let client_clone = Arc::clone(client);
+                        self.send_capabilities(client_clone, HeaderType::Reconnect, &capabilities)
+                            .await;
+                    }
+
+                    if let Some(last_sequence) = request.last_sequence {
+                        client.acknowledge(last_sequence).await;
+                    }
+                    self.send_missed_packets(Arc::clone(client)).await;
 
                     Ok(())
                 }
             }
         } else {
-            Err(PlayerConnectionError::PlayerNotConnected)
+            let error = PlayerConnectionError::PlayerNotConnected;
+            Err(self.reject_temp_client(temp_client, error).await)
+        }
+    }
+
+    /// Rejects a temporary client whose protocol version is not supported.
+    ///
+    /// Sends an `UnsupportedProtocolVersion` packet carrying the supported version range and closes the connection.
+    ///
+    /// # Arguments
+    /// * `temp_client` - The temporary client being rejected.
+    /// * `protocol_version` - The version requested by the client.
+    ///
+    /// # Returns
+    /// The `PlayerConnectionError` describing the rejection.
+    async fn reject_version(
+        &self,
+        temp_client: Arc<TemporaryClient>,
+        protocol_version: Option<u16>,
+    ) -> PlayerConnectionError {
+        let protocol_version = protocol_version.unwrap_or_default();
+        logger!(
+            WARN,
+            "[PROTOCOL] Client `{}` requested unsupported protocol version `{protocol_version}`",
+            &temp_client.addr
+        );
+
+        let error = PlayerConnectionError::UnsupportedProtocolVersion(protocol_version);
+        let packet = Packet::error(HeaderType::UnsupportedProtocolVersion, &error);
+        Self::send_rejection(temp_client, &packet, error).await
+    }
+
+    /// Rejects a temporary client that could not be authenticated.
+    ///
+    /// Sends an `ERROR` packet carrying the error envelope and closes the connection.
+    ///
+    /// # Arguments
+    /// * `temp_client` - The temporary client being rejected.
+    /// * `error` - The reason of the rejection.
+    ///
+    /// # Returns
+    /// The `PlayerConnectionError` describing the rejection.
+    async fn reject_temp_client(
+        &self,
+        temp_client: Arc<TemporaryClient>,
+        error: PlayerConnectionError,
+    ) -> PlayerConnectionError {
+        let packet = Packet::error(HeaderType::ERROR, &error);
+        Self::send_rejection(temp_client, &packet, error).await
+    }
+
+    /// Sends the rejection packet to a temporary client and closes the connection.
+    ///
+    /// # Returns
+    /// The given error, or an `InternalError` if the temporary client cannot be unwrapped.
+    async fn send_rejection(
+        temp_client: Arc<TemporaryClient>,
+        packet: &Packet,
+        error: PlayerConnectionError,
+    ) -> PlayerConnectionError {
+        match Arc::try_unwrap(temp_client) {
+            Ok(temp) => {
+                temp.reject(packet).await;
+                error
+            }
+            Err(_) => PlayerConnectionError::InternalError(
+                "Unable to unwrap temporary client".to_string(),
+            ),
+        }
+    }
+
+    /// Answers a versioned `Connect`/`Reconnect` request with the negotiated capabilities.
+    ///
+    /// # Arguments
+    /// * `client` - The client that has just been (re)connected.
+    /// * `header_type` - The header type of the request being answered.
+    /// * `capabilities` - The protocol version and features agreed for the connection.
+    async fn send_capabilities(
+        &self,
+        client: Arc<Client>,
+        header_type: HeaderType,
+        capabilities: &Capabilities,
+    ) {
+        match serde_cbor::to_vec(capabilities) {
+            Ok(payload) => {
+                let packet = Packet::new(header_type, &payload);
+                self.send_control_packet(client, &packet).await;
+            }
+            Err(error) => logger!(
+                ERROR,
+                "[PROTOCOL] Unable to serialize capabilities ({error})"
+            ),
         }
     }
 
@@ -301,68 +676,408 @@ impl Protocol {
     /// - Retrieves the full card data (fetching from an external source if necessary).
     /// - Executes the card’s `on_play` triggers via the Lua scripting engine.
     ///
+    /// The client is always answered with an `ActionResponse` echoing the request id, whether
+    /// the card was played or not.
+    ///
     /// # Arguments
     /// * `client` - The client attempting to play the card.
-    /// * `request` - The play card request containing the player and card ID.
+    /// * `request` - The decoded `PlayCardRequest`.
+    async fn handle_play_card(&self, client: Arc<Client>, request: &PlayCardRequest) {
+        let response = match self
+            .game_instance
+            .clone()
+            .play_card(client.clone(), request)
+            .await
+        {
+            Ok(events) => {
+                logger!(INFO, "Play card request was finished successfully");
+                ActionResponse::accepted(request.request_id, events)
+            }
+            Err(error) => {
+                logger!(ERROR, "Play Card Request: {}", error.to_string());
+                ActionResponse::rejected(request.request_id, ErrorEnvelope::from(&error))
+            }
+        };
+        self.send_action_response(client, PlayCard, &response).await;
+    }
+
+    /// Handles an attack from a client, answering with an `ActionResponse`.
     ///
-    /// # Returns
-    /// * `Ok(())` if the action is successful.
-    /// * `Err(GameLogicError)` if any validation or execution step fails.
-    async fn handle_play_card(&self, client: Arc<Client>, packet: &Packet) {
-        logger!(DEBUG, "Handle play card ended");
-        match serde_cbor::from_slice::<PlayCardRequest>(&packet.payload) {
-            Ok(request) => {
-                if let Err(error) = self
-                    .game_instance
-                    .clone()
-                    .play_card(client.clone(), &request)
-                    .await
-                {
-                    let error_message = error.to_string();
-                    logger!(ERROR, "Play Card Request: {}", error_message.clone());
-                    let error_packet = Packet::new(HeaderType::PlayCard, error_message.as_bytes());
-                    let _ = self.send_packet(client, &error_packet).await;
-                } else {
-                    logger!(INFO, "Play card request was finished successfully");
-                }
+    /// # Arguments
+    /// * `client` - The client attempting the attack.
+    /// * `request` - The decoded `AttackRequest`.
+    async fn handle_attack(&self, client: Arc<Client>, request: &AttackRequest) {
+        let response = match self
+            .game_instance
+            .clone()
+            .attack(client.clone(), request)
+            .await
+        {
+            Ok(events) => ActionResponse::accepted(request.request_id, events),
+            Err(error) => {
+                logger!(ERROR, "[PROTOCOL] Attack request: {error}");
+                ActionResponse::rejected(request.request_id, ErrorEnvelope::from(&error))
             }
+        };
+        self.send_action_response(client, HeaderType::AttackPlayer, &response).await;
+    }
+
+    /// Handles a client ending their turn, answering with an `ActionResponse`.
+    ///
+    /// # Arguments
+    /// * `client` - The client ending their turn.
+    /// * `request` - The decoded `EndTurnRequest`.
+    async fn handle_end_turn(&self, client: Arc<Client>, request: &EndTurnRequest) {
+        let response = match self
+            .game_instance
+            .clone()
+            .end_turn(client.clone(), request)
+            .await
+        {
+            Ok(events) => ActionResponse::accepted(request.request_id, events),
             Err(error) => {
-                let error_message = error.to_string();
-                logger!(
-                    ERROR,
-                    "[PROTOCOL] Play card request: {}",
-                    error_message.clone()
-                );
-                let error_packet = Packet::new(HeaderType::PlayCard, error_message.as_bytes());
-                let _ = self.send_packet(client, &error_packet).await;
+                logger!(ERROR, "[PROTOCOL] End turn request: {error}");
+                ActionResponse::rejected(request.request_id, ErrorEnvelope::from(&error))
+            }
+        };
+        self.send_action_response(client, HeaderType::EndTurn, &response).await;
+    }
+
+    /// Handles a client returning cards from their opening hand, answering with an
+    /// `ActionResponse`.
+    ///
+    /// # Arguments
+    /// * `client` - The client sending their mulligan.
+    /// * `request` - The decoded `MulliganRequest`.
+    async fn handle_mulligan(&self, client: Arc<Client>, request: &MulliganRequest) {
+        let response = match self
+            .game_instance
+            .clone()
+            .mulligan(client.clone(), request)
+            .await
+        {
+            Ok(events) => ActionResponse::accepted(request.request_id, events),
+            Err(error) => {
+                logger!(ERROR, "[PROTOCOL] Mulligan request: {error}");
+                ActionResponse::rejected(request.request_id, ErrorEnvelope::from(&error))
+            }
+        };
+        self.send_action_response(client, HeaderType::Mulligan, &response).await;
+    }
+
+    /// Handles a client giving up the match, answering with an `ActionResponse`.
+    ///
+    /// # Arguments
+    /// * `client` - The client conceding.
+    /// * `request` - The decoded `ConcedeRequest`.
+    async fn handle_concede(&self, client: Arc<Client>, request: &ConcedeRequest) {
+        let response = match self
+            .game_instance
+            .clone()
+            .concede(client.clone(), request)
+            .await
+        {
+            Ok(events) => ActionResponse::accepted(request.request_id, events),
+            Err(error) => {
+                logger!(ERROR, "[PROTOCOL] Concede request: {error}");
+                ActionResponse::rejected(request.request_id, ErrorEnvelope::from(&error))
+            }
+        };
+        self.send_action_response(client, HeaderType::Concede, &response).await;
+    }
+
+    /// Ends the match once it has a result: the result is sent to every client in a final
+    /// `MatchEnded` packet, after which they are disconnected, and the server is stopped with
+    /// `ExitCode::MatchEnded`.
+    ///
+    /// Does nothing while the match is ongoing, or once it has already ended.
+    async fn check_match_end(&self) {
+        let Some(result) = self.game_instance.match_result().await else {
+            return;
+        };
+
+        let status = ExitStatus {
+            code: ExitCode::MatchEnded as i32,
+            reason: result.to_string(),
+        };
+        if !self.server_instance.stop(status).await {
+            return;
+        }
+
+        let payload = match serde_cbor::to_vec(&result) {
+            Ok(payload) => payload,
+            Err(error) => {
+                logger!(ERROR, "[PROTOCOL] Unable to serialize the match result ({error})");
+                return;
+            }
+        };
+
+        let packet = Packet::new(HeaderType::MatchEnded, &payload);
+        let clients: Vec<_> = self
+            .server_instance
+            .connected_clients
+            .read()
+            .await
+            .values()
+            .cloned()
+            .collect();
+        for client in clients {
+            self.send_and_disconnect(client, &packet).await;
+        }
+    }
+
+    /// Ends the match as soon as a change of the game state gives it a result, including the
+    /// changes made outside of client actions, such as the end of the mulligan phase or the
+    /// start of a turn.
+    ///
+    /// This function runs until the match has ended.
+    pub async fn watch_match_end(self: Arc<Self>) {
+        let mut changes = self.game_instance.state_changed.subscribe();
+        changes.mark_changed();
+        while changes.changed().await.is_ok() {
+            self.check_match_end().await;
+            if self.server_instance.exit_status.read().await.is_some() {
+                break;
+            }
+        }
+    }
+
+    /// Answers a client action with its `ActionResponse`.
+    ///
+    /// # Arguments
+    /// * `client` - The client that sent the action.
+    /// * `header_type` - The header type of the action being answered.
+    /// * `response` - The outcome of the action.
+    async fn send_action_response(
+        &self,
+        client: Arc<Client>,
+        header_type: HeaderType,
+        response: &ActionResponse,
+    ) {
+        match serde_cbor::to_vec(response) {
+            Ok(payload) => {
+                let packet = Packet::new(header_type, &payload);
+                let _ = self.send_packet(client, &packet).await;
             }
+            Err(error) => logger!(
+                ERROR,
+                "[PROTOCOL] Unable to serialize action response ({error})"
+            ),
         }
     }
 
     /// Sends any missed packets to the client.
     ///
-    /// This function retrieves the missed packets from the client's queue and sends them one by one.
-    /// It uses a loop to send each packet, waiting for a short duration between sending to avoid overwhelming the client.
+    /// The resend is queued behind the packets already waiting to be written, so the client
+    /// receives the answer to its reconnection first.
     ///
     /// # Arguments
     /// * `client` - The client to which the missed packets should be sent.
     pub async fn send_missed_packets(&self, client: Arc<Client>) {
-        let mut packets_lock = client.missed_packets.write().await;
-        loop {
-            if let Some(packet) = packets_lock.pop_front() {
-                let client_clone = Arc::clone(&client);
-                self.send_or_disconnect(client_clone, &packet).await;
-                tokio::time::interval(Duration::from_micros(30))
-                    .tick()
-                    .await;
-            } else {
-                break;
+        let _ = self.queue_outbound(client, Outbound::ResendMissed).await;
+    }
+
+    /// Resends the packets the client has not acknowledged yet. Only called by the client's writer task.
+    ///
+    /// This function resends the packets in sequence order, keeping their original sequence ids.
+    /// Clients that did not negotiate sequencing cannot acknowledge packets, so their queue is
+    /// emptied once it has been sent.
+    /// It waits for a short duration between sending to avoid overwhelming the client.
+    ///
+    /// # Arguments
+    /// * `client` - The client to which the missed packets should be sent.
+    /// * `capabilities` - The capabilities negotiated for the connection.
+    async fn resend_missed_packets(&self, client: Arc<Client>, capabilities: &Capabilities) {
+        let mut stream_guard = client.write_stream.write().await;
+        let addr = *client.addr.read().await;
+
+        let packets: Vec<Packet> = {
+            let mut packets_lock = client.missed_packets.write().await;
+            match capabilities.sequencing {
+                true => packets_lock.iter().cloned().collect(),
+                false => packets_lock.drain(..).collect(),
             }
+        };
+
+        for packet in &packets {
+            if self
+                .write_packet(&mut stream_guard, &addr, packet, capabilities)
+                .await
+                .is_err()
+            {
+                drop(stream_guard);
+                self.disconnect(client).await;
+                return;
+            }
+            tokio::time::interval(Duration::from_micros(30))
+                .tick()
+                .await;
         }
+
         logger!(
             INFO,
-            "[PROTOCOL] Sent latest missed packets to {}",
-            &client.addr.read().await
+            "[PROTOCOL] Sent {} missed packets to {addr}",
+            packets.len()
         )
     }
+
+    /// Handles an acknowledgement from the client, dropping every packet it has already received.
+    ///
+    /// # Arguments
+    /// * `client` - The client acknowledging the packets.
+    /// * `request` - The request containing the last received sequence id.
+    async fn handle_ack(&self, client: Arc<Client>, request: &AcknowledgeRequest) {
+        client.acknowledge(request.sequence).await
+    }
+
+    /// Sends a heartbeat `Ping` carrying the server time to the client.
+    ///
+    /// # Arguments
+    /// * `client` - The client to ping.
+    pub async fn send_ping(&self, client: Arc<Client>) {
+        let ping = PingMessage {
+            sent_at: Utc::now().timestamp_millis(),
+        };
+
+        match serde_cbor::to_vec(&ping) {
+            Ok(payload) => {
+                let packet = Packet::new(HeaderType::Ping, &payload);
+                self.send_control_packet(client, &packet).await;
+            }
+            Err(error) => logger!(ERROR, "[PROTOCOL] Unable to serialize ping ({error})"),
+        }
+    }
+
+    /// Answers a client's `Ping` with a `Pong` carrying the server time.
+    ///
+    /// Pings without a payload are answered as well, echoing a zero timestamp.
+    ///
+    /// # Arguments
+    /// * `client` - The client that sent the ping.
+    /// * `ping` - The client's `PingMessage`, defaulted when the packet had no payload.
+    async fn handle_ping(&self, client: Arc<Client>, ping: &PingMessage) {
+        let received_at = Utc::now().timestamp_millis();
+        let pong = PongMessage {
+            ping_sent_at: ping.sent_at,
+            received_at,
+            sent_at: Utc::now().timestamp_millis(),
+        };
+
+        match serde_cbor::to_vec(&pong) {
+            Ok(payload) => {
+                let packet = Packet::new(HeaderType::Pong, &payload);
+                self.send_control_packet(client, &packet).await;
+            }
+            Err(error) => logger!(ERROR, "[PROTOCOL] Unable to serialize pong ({error})"),
+        }
+    }
+
+    /// Handles a client's `Pong` answering a server heartbeat.
+    ///
+    /// - Records the round-trip time and clock offset in the client's rolling statistics.
+    /// - Exposes the updated statistics in the player's own view of the game state, which is
+    ///   pushed to the client whenever they change.
+    /// - Logs a warning when the round-trip time exceeds the configured threshold.
+    ///
+    /// # Arguments
+    /// * `client` - The client that answered the ping.
+    /// * `pong` - The client's `PongMessage`.
+    async fn handle_pong(&self, client: Arc<Client>, pong: &PongMessage) {
+        let received_at = Utc::now().timestamp_millis();
+
+        let (round_trip, average_round_trip, clock_offset) = {
+            let mut latency = client.latency.write().await;
+            let round_trip = latency.record(pong, received_at);
+            (
+                round_trip,
+                latency.round_trip_ms(),
+                latency.clock_offset_ms(),
+            )
+        };
+
+        let changed = {
+            let player = client.player.read().await;
+            let mut player_view = player.player_view.write().await;
+            let changed = player_view.ping_ms != average_round_trip
+                || player_view.clock_offset_ms != clock_offset;
+            player_view.ping_ms = average_round_trip;
+            player_view.clock_offset_ms = clock_offset;
+            changed
+        };
+        if changed {
+            self.game_instance.notify_change();
+        }
+
+        let settings = SETTINGS.get().expect("Settings not initialized");
+        if round_trip > settings.latency_warning_ms {
+            let addr = client.addr.read().await;
+            logger!(
+                WARN,
+                "[PROTOCOL] `{addr}` round trip of {round_trip}ms exceeds {}ms (average: {}ms)",
+                settings.latency_warning_ms,
+                average_round_trip.unwrap_or(round_trip)
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::time::sleep;
+
+    #[tokio::test]
+    async fn test_handle_pong() {
+        let protocol = Arc::new(Protocol::for_tests().await);
+        let (client, _remote) = Client::for_tests(&protocol, "red", Capabilities::default()).await;
+
+        // The client's clock is a second ahead of the server's
+        let now = Utc::now().timestamp_millis();
+        let pong = PongMessage {
+            ping_sent_at: now - 40,
+            received_at: now - 20 + 1000,
+            sent_at: now - 20 + 1000,
+        };
+        let changes = protocol.game_instance.state_changed.subscribe();
+        protocol.handle_pong(client, &pong).await;
+        assert!(changes.has_changed().unwrap());
+
+        // Only the player sees their own connection statistics
+        let game_state = protocol.game_instance.game_state.read().await;
+        let red_player = game_state.private_view("red").await.red_player;
+        assert!(red_player.ping_ms.is_some_and(|ping| ping >= 40));
+        assert!(red_player
+            .clock_offset_ms
+            .is_some_and(|offset| (990..=1010).contains(&offset)));
+
+        let red_player = game_state.private_view("blue").await.red_player;
+        assert!(red_player.ping_ms.is_none() && red_player.clock_offset_ms.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_match_ends_outside_of_actions() {
+        let protocol = Arc::new(Protocol::for_tests().await);
+        tokio::spawn(Arc::clone(&protocol).watch_match_end());
+        sleep(Duration::from_millis(20)).await;
+        assert!(protocol.server_instance.exit_status.read().await.is_none());
+
+        // The red hero falls without any action, then the mulligan phase opens
+        {
+            let game_state = protocol.game_instance.game_state.read().await;
+            let (red_player, _) = game_state.player_and_opponent("red").await.unwrap();
+            red_player.write().await.health = 0;
+        }
+        Arc::clone(&protocol.game_instance).start_game().await;
+
+        for _ in 0..100 {
+            let exit_status = protocol.server_instance.exit_status.read().await;
+            if let Some(exit_status) = exit_status.as_ref() {
+                assert_eq!(exit_status.code, ExitCode::MatchEnded as i32);
+                return;
+            }
+            drop(exit_status);
+            sleep(Duration::from_millis(10)).await;
+        }
+        panic!("The match did not end");
+    }
 }
@@ -0,0 +1,296 @@
+use crate::game::game_state::{PrivateGameStateView, PublicGameStateView};
+use crate::models::action_response::ActionResponse;
+use crate::models::error_response::ErrorEnvelope;
+use crate::models::heartbeat::{PingMessage, PongMessage};
+use crate::models::match_result::MatchResult;
+use crate::tcp::handshake::{Capabilities, MIN_PROTOCOL_VERSION, PROTOCOL_VERSION};
+use crate::tcp::header::{
+    HeaderType, CHECKSUM_EXTENSION_SIZE, COMPRESSED_FLAG, EXTENDED_LENGTH_MARKER,
+    EXTENDED_LENGTH_SIZE, HEADER_SIZE, MAX_LEGACY_PAYLOAD_LENGTH, MAX_PAYLOAD_LENGTH,
+    SEQUENCED_FLAG, SEQUENCE_SIZE,
+};
+use crate::tcp::message::Message;
+use crate::utils::checksum::ChecksumAlgorithm;
+use crate::utils::compression::COMPRESSION_THRESHOLD;
+use schemars::generate::SchemaSettings;
+use schemars::{Schema, SchemaGenerator};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::io::Error;
+
+/// Machine-readable description of the whole protocol, exported for client code generation.
+///
+/// Payload schemas are JSON Schema (draft 2020-12) and reference the shared `$defs`.
+#[derive(Serialize)]
+pub struct ProtocolSchema {
+    pub protocol_version: u16,
+    pub min_protocol_version: u16,
+    pub header: HeaderLayout,
+    pub messages: Vec<MessageSchema>,
+    pub views: Vec<NamedSchema>,
+    #[serde(rename = "$defs")]
+    pub definitions: Map<String, Value>,
+}
+
+/// Layout of the packet header, in transmission order.
+#[derive(Serialize)]
+pub struct HeaderLayout {
+    pub size: usize,
+    pub fields: Vec<HeaderField>,
+    pub flags: Vec<NamedValue>,
+    pub checksum_algorithms: Vec<NamedValue>,
+    pub extended_length_marker: u16,
+    pub max_legacy_payload_length: u32,
+    pub max_payload_length: u32,
+    pub compression_threshold: usize,
+}
+
+/// A single header field. Optional fields carry the condition under which they are present.
+#[derive(Serialize)]
+pub struct HeaderField {
+    pub name: &'static str,
+    pub size: usize,
+    pub condition: Option<&'static str>,
+}
+
+#[derive(Serialize)]
+pub struct NamedValue {
+    pub name: String,
+    pub value: u8,
+}
+
+#[derive(Serialize)]
+pub struct NamedSchema {
+    pub name: &'static str,
+    pub schema: Schema,
+}
+
+/// A message type along with the payloads it carries in each direction.
+///
+/// `client_payload` is `None` for messages the server does not accept from clients, or that
+/// have no payload.
+#[derive(Serialize)]
+pub struct MessageSchema {
+    pub name: String,
+    pub code: u8,
+    pub action: bool,
+    pub client: bool,
+    pub client_payload: Option<Schema>,
+    pub server_payload: Option<Schema>,
+}
+
+impl ProtocolSchema {
+    /// Builds the schema of the protocol spoken by this server.
+    pub fn generate() -> Self {
+        let mut generator = SchemaGenerator::new(SchemaSettings::draft2020_12());
+        let mut client_payloads = Message::payload_schemas(&mut generator);
+
+        let messages = (0..=u8::MAX)
+            .filter_map(|code| HeaderType::try_from(code).ok())
+            .map(|header_type| {
+                let client_payload = client_payloads
+                    .iter()
+                    .position(|(registered, _)| *registered == header_type)
+                    .map(|index| client_payloads.swap_remove(index).1);
+
+                MessageSchema {
+                    name: header_type.to_string(),
+                    code: header_type.clone() as u8,
+                    action: Message::is_action(&header_type),
+                    client: client_payload.is_some(),
+                    client_payload: client_payload.flatten(),
+                    server_payload: server_payload(&header_type, &mut generator),
+                }
+            })
+            .collect();
+
+        let views = vec![
+            NamedSchema {
+                name: "PrivateGameStateView",
+                schema: generator.subschema_for::<PrivateGameStateView>(),
+            },
+            NamedSchema {
+                name: "PublicGameStateView",
+                schema: generator.subschema_for::<PublicGameStateView>(),
+            },
+        ];
+
+        Self {
+            protocol_version: PROTOCOL_VERSION,
+            min_protocol_version: MIN_PROTOCOL_VERSION,
+            header: HeaderLayout::current(),
+            messages,
+            views,
+            definitions: generator.take_definitions(true),
+        }
+    }
+}
+
+impl HeaderLayout {
+    fn current() -> Self {
+        Self {
+            size: HEADER_SIZE,
+            fields: vec![
+                HeaderField::always("message_type", 1),
+                HeaderField::always("payload_length", 2),
+                HeaderField::always("checksum", 2),
+                HeaderField::always("end_byte", 1),
+                HeaderField {
+                    name: "checksum_low",
+                    size: CHECKSUM_EXTENSION_SIZE,
+                    condition: Some("checksum algorithm is CRC32"),
+                },
+                HeaderField {
+                    name: "sequence",
+                    size: SEQUENCE_SIZE,
+                    condition: Some("SEQUENCED flag is set"),
+                },
+                HeaderField {
+                    name: "extended_length",
+                    size: EXTENDED_LENGTH_SIZE,
+                    condition: Some("payload_length equals extended_length_marker"),
+                },
+            ],
+            flags: vec![
+                NamedValue {
+                    name: "SEQUENCED".to_string(),
+                    value: SEQUENCED_FLAG,
+                },
+                NamedValue {
+                    name: "COMPRESSED".to_string(),
+                    value: COMPRESSED_FLAG,
+                },
+            ],
+            checksum_algorithms: [
+                ChecksumAlgorithm::Xor,
+                ChecksumAlgorithm::Crc16,
+                ChecksumAlgorithm::Crc32,
+            ]
+            .into_iter()
+            .map(|algorithm| NamedValue {
+                name: format!("{algorithm:?}").to_uppercase(),
+                value: algorithm as u8,
+            })
+            .collect(),
+            extended_length_marker: EXTENDED_LENGTH_MARKER,
+            max_legacy_payload_length: MAX_LEGACY_PAYLOAD_LENGTH,
+            max_payload_length: MAX_PAYLOAD_LENGTH,
+            compression_threshold: COMPRESSION_THRESHOLD,
+        }
+    }
+}
+
+impl HeaderField {
+    fn always(name: &'static str, size: usize) -> Self {
+        Self {
+            name,
+            size,
+            condition: None,
+        }
+    }
+}
+
+/// Returns the schema of the payload the server sends with the given header type.
+///
+/// # Arguments
+/// * `header_type` - The header type of the server packet.
+/// * `generator` - The generator collecting the definitions referenced by the schema.
+fn server_payload(header_type: &HeaderType, generator: &mut SchemaGenerator) -> Option<Schema> {
+    match header_type {
+        HeaderType::Connect | HeaderType::Reconnect => {
+            Some(generator.subschema_for::<Capabilities>())
+        }
+        HeaderType::Ping => Some(generator.subschema_for::<PingMessage>()),
+        HeaderType::Pong => Some(generator.subschema_for::<PongMessage>()),
+        HeaderType::GameState => Some(generator.subschema_for::<PrivateGameStateView>()),
+        HeaderType::PlayCard
+        | HeaderType::AttackPlayer
+        | HeaderType::EndTurn
+        | HeaderType::Mulligan
+        | HeaderType::Concede => Some(generator.subschema_for::<ActionResponse>()),
+        HeaderType::MatchEnded => Some(generator.subschema_for::<MatchResult>()),
+        HeaderType::InvalidHeader
+        | HeaderType::AlreadyConnected
+        | HeaderType::InvalidPlayerData
+        | HeaderType::InvalidChecksum
+        | HeaderType::FailedToConnectPlayer
+        | HeaderType::InvalidPacketPayload
+        | HeaderType::UnsupportedProtocolVersion
+        | HeaderType::ERROR => Some(generator.subschema_for::<ErrorEnvelope>()),
+        HeaderType::Disconnect | HeaderType::Ack | HeaderType::InitServer => None,
+    }
+}
+
+/// Writes the protocol schema as pretty-printed JSON.
+///
+/// # Arguments
+/// * `path` - The file to write to, or `None` to print to the standard output.
+pub fn export(path: Option<&str>) -> Result<(), Error> {
+    let schema = serde_json::to_string_pretty(&ProtocolSchema::generate())?;
+    match path {
+        Some(path) => std::fs::write(path, schema),
+        None => {
+            println!("{schema}");
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tcp::header::Header;
+
+    #[test]
+    fn test_schema_covers_messages() {
+        let schema = ProtocolSchema::generate();
+        let play_card = schema
+            .messages
+            .iter()
+            .find(|message| message.code == HeaderType::PlayCard as u8)
+            .unwrap();
+
+        assert!(play_card.action);
+        assert!(play_card.client_payload.is_some());
+        assert!(schema.definitions.contains_key("PlayCardRequest"));
+        assert!(schema.definitions.contains_key("PlayerView"));
+
+        // Server-only messages have no client payload
+        let game_state = schema
+            .messages
+            .iter()
+            .find(|message| message.code == HeaderType::GameState as u8)
+            .unwrap();
+        assert!(!game_state.client);
+        assert!(game_state.server_payload.is_some());
+    }
+
+    #[test]
+    fn test_header_layout_matches_wrap_header() {
+        let payload = [0x01; 40_000];
+        let mut header =
+            Header::with_algorithm(HeaderType::GameState, &payload, ChecksumAlgorithm::Crc32);
+        header.sequence = Some(42);
+        let bytes = header.wrap_header();
+        let checksum = header.checksum.to_be_bytes();
+
+        // Every optional field is present, so each one sits right after the previous one
+        let mut offset = 0;
+        for field in HeaderLayout::current().fields {
+            let value = &bytes[offset..offset + field.size];
+            let expected: Vec<u8> = match field.name {
+                "message_type" => vec![HeaderType::GameState as u8],
+                "payload_length" => EXTENDED_LENGTH_MARKER.to_be_bytes().to_vec(),
+                "checksum" => checksum[..2].to_vec(),
+                "end_byte" => vec![ChecksumAlgorithm::Crc32 as u8 | SEQUENCED_FLAG],
+                "checksum_low" => checksum[2..].to_vec(),
+                "sequence" => 42u32.to_be_bytes().to_vec(),
+                "extended_length" => 40_000u32.to_be_bytes().to_vec(),
+                name => panic!("Unexpected header field `{name}`"),
+            };
+            assert_eq!(value, expected.as_slice(), "field `{}`", field.name);
+            offset += field.size;
+        }
+        assert_eq!(offset, bytes.len());
+    }
+}
@@ -3,16 +3,24 @@ use crate::game::game::GameInstance;
 use crate::models::exit_code::ExitStatus;
 use crate::models::init_server::InitServerRequest;
 use crate::tcp::client::TemporaryClient;
+use crate::tcp::decoder::PacketDecoder;
 use crate::tcp::header::HeaderType;
+use crate::tcp::message::Message;
 use crate::tcp::packet::Packet;
 use crate::tcp::protocol::Protocol;
+use crate::tcp::transport::{self, BoxedTransport};
+use crate::tcp::tls;
+use crate::tcp::websocket::WebSocketTransport;
 use crate::utils::errors::ServerInstanceError;
-use crate::{logger, utils::logger::Logger, SERVER_INSTANCE};
+use crate::{logger, utils::logger::Logger, SERVER_INSTANCE, SETTINGS};
 use std::collections::HashMap;
 use std::{io::Error, net::Ipv4Addr, sync::Arc};
+use std::time::Duration;
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
-use tokio::net::TcpStream;
+use tokio::sync::watch;
+use tokio::time::{sleep, Instant};
 use tokio::{net::TcpListener, sync::RwLock};
+use tokio_rustls::TlsAcceptor;
 
 static HOST: Ipv4Addr = Ipv4Addr::new(127, 0, 0, 1);
 
@@ -25,6 +33,8 @@ pub struct ServerInstance {
     pub game_instance: Arc<GameInstance>,
     pub exit_status: Arc<RwLock<Option<ExitStatus>>>, // The exit status of the server.
     pub connected_clients: Arc<RwLock<HashMap<String, Arc<Client>>>>, // A map of connected players, identified by their unique IDs.
+    pub tls_acceptor: Option<TlsAcceptor>, // Wraps accepted connections in TLS when configured.
+    pub shutdown: watch::Sender<bool>, // Wakes up the listen loops once the server stops.
 }
 
 impl ServerInstance {
@@ -36,13 +46,15 @@ impl ServerInstance {
             true => Err(ServerInstanceError::AlreadyInitialized),
             false => {
                 if let Ok(server) = Arc::try_unwrap(uninitialized) {
-                    match GameInstance::create_instance(request.players).await {
+                    match GameInstance::create_instance(request.players, request.seed).await {
                         Ok(game_instance) => Ok(ServerInstance {
                             socket: server.socket,
                             game_instance: Arc::new(game_instance),
                             exit_status: Arc::new(RwLock::new(None)),
-                            listening: Arc::new(RwLock::new(false)),
+                            listening: Arc::new(RwLock::new(true)),
                             connected_clients: Arc::new(RwLock::new(HashMap::new())),
+                            tls_acceptor: server.tls_acceptor,
+                            shutdown: watch::channel(false).0,
                         }),
                         Err(error) => Err(ServerInstanceError::GameInstanceFail(error.to_string())),
                     }
@@ -55,29 +67,116 @@ impl ServerInstance {
 
     /// Starts the main server loop and handles incoming client connections.
     ///
-    /// - Spawns a background task to broadcast game state updates.
+    /// - Spawns a background task that ends the match once the game state gives it a result.
     /// - Accepts new TCP clients, logs them, registers them, and spawns their handling task.
+    /// - Performs the TLS handshake within the client's task when TLS is enabled.
+    /// - Spawns a WebSocket listener on `WEBSOCKET_PORT` when configured.
     ///
-    /// Runs indefinitely. Requires `self` as `Arc` for shared access.
+    /// Runs until the server is stopped at the end of the match. Requires `self` as `Arc` for
+    /// shared access.
     pub async fn listen(self: Arc<Self>) {
         let protocol = Arc::new(Protocol::new(self.clone(), self.game_instance.clone()));
 
-        // Spawn a background task to handle game state updates.
-        // tokio::spawn({
-        //     let protocol_clone = Arc::clone(&protocol);
-        //     async move { protocol_clone.cycle_game_state().await }
-        // });
+        let settings = SETTINGS.get().expect("Settings not initialized");
+        if let Some(port) = settings.websocket_port {
+            tokio::spawn({
+                let self_clone = Arc::clone(&self);
+                let protocol_clone = Arc::clone(&protocol);
+                async move { self_clone.listen_websocket(port, protocol_clone).await }
+            });
+        }
+
+        // Spawn a background task to end the match once the game state gives it a result.
+        tokio::spawn(Arc::clone(&protocol).watch_match_end());
 
         // Main loop to accept and handle incoming client connections.
+        let mut shutdown = self.shutdown.subscribe();
         while *self.listening.read().await {
-            match self.socket.accept().await {
+            let accepted = tokio::select! {
+                accepted = self.socket.accept() => accepted,
+                _ = shutdown.changed() => break,
+            };
+
+            match accepted {
                 Err(error) => logger!(INFO, "[SERVER] Failed to accept client connection: {error}"),
                 Ok((stream, addr)) => {
                     logger!(INFO, "[CONNECTION] Accepted request from `{addr}`");
                     let protocol_clone = Arc::clone(&protocol);
+                    let tls_acceptor = self.tls_acceptor.clone();
 
                     // Spawn a task to handle the temporary client.
                     tokio::spawn(async move {
+                        let stream = match transport::accept(stream, tls_acceptor.as_ref()).await {
+                            Ok(stream) => stream,
+                            Err(error) => {
+                                logger!(
+                                    WARN,
+                                    "[CONNECTION] TLS handshake with `{addr}` failed: {error}"
+                                );
+                                return;
+                            }
+                        };
+                        let temp_client = TemporaryClient::new(stream, addr, protocol_clone).await;
+                        temp_client.handle_temp_client().await;
+                    });
+                }
+            }
+        }
+    }
+
+    /// Accepts WebSocket clients on a second port.
+    ///
+    /// Upgraded connections carry the same packets in binary frames and are handed to the
+    /// same `Protocol` as TCP clients, so the rest of the server is unaware of the transport.
+    ///
+    /// # Arguments
+    /// * `port` - The port to listen on for WebSocket connections.
+    /// * `protocol` - The protocol shared with the TCP listener.
+    async fn listen_websocket(self: Arc<Self>, port: u16, protocol: Arc<Protocol>) {
+        let listener = match TcpListener::bind((HOST, port)).await {
+            Ok(listener) => listener,
+            Err(error) => {
+                logger!(
+                    ERROR,
+                    "[SERVER] Unable to listen for WebSockets on port `{port}`: {error}"
+                );
+                return;
+            }
+        };
+        logger!(INFO, "[SERVER] Listening for WebSockets on port `{port}`");
+
+        let mut shutdown = self.shutdown.subscribe();
+        while *self.listening.read().await {
+            let accepted = tokio::select! {
+                accepted = listener.accept() => accepted,
+                _ = shutdown.changed() => break,
+            };
+
+            match accepted {
+                Err(error) => {
+                    logger!(INFO, "[SERVER] Failed to accept WebSocket connection: {error}")
+                }
+                Ok((stream, addr)) => {
+                    logger!(INFO, "[CONNECTION] Accepted WebSocket request from `{addr}`");
+                    let protocol_clone = Arc::clone(&protocol);
+                    let tls_acceptor = self.tls_acceptor.clone();
+
+                    tokio::spawn(async move {
+                        let upgraded =
+                            match transport::accept(stream, tls_acceptor.as_ref()).await {
+                                Ok(stream) => WebSocketTransport::accept(stream).await,
+                                Err(error) => Err(error),
+                            };
+                        let stream: BoxedTransport = match upgraded {
+                            Ok(websocket) => Box::new(websocket),
+                            Err(error) => {
+                                logger!(
+                                    WARN,
+                                    "[CONNECTION] WebSocket handshake with `{addr}` failed: {error}"
+                                );
+                                return;
+                            }
+                        };
                         let temp_client = TemporaryClient::new(stream, addr, protocol_clone).await;
                         temp_client.handle_temp_client().await;
                     });
@@ -87,19 +186,93 @@ impl ServerInstance {
     }
 }
 
+// Shutdown implementations
+impl ServerInstance {
+    /// Records the exit status of the server and stops its listen loops.
+    ///
+    /// # Returns
+    /// `false` if the server was already stopped, in which case the first status is kept.
+    pub async fn stop(&self, status: ExitStatus) -> bool {
+        let mut exit_status = self.exit_status.write().await;
+        if exit_status.is_some() {
+            return false;
+        }
+
+        logger!(INFO, "[SERVER] Stopping with code {}: {}", status.code, status.reason);
+        *exit_status = Some(status);
+        *self.listening.write().await = false;
+        self.shutdown.send_replace(true);
+        true
+    }
+
+    /// Waits until every client is disconnected, so that their final packets are written,
+    /// for at most `timeout`.
+    pub async fn drain_clients(&self, timeout: Duration) {
+        let deadline = Instant::now() + timeout;
+        while Instant::now() < deadline {
+            let mut connected = false;
+            for client in self.connected_clients.read().await.values() {
+                connected |= *client.connected.read().await;
+            }
+            if !connected {
+                return;
+            }
+            sleep(Duration::from_millis(50)).await;
+        }
+    }
+}
+
+#[cfg(test)]
+impl ServerInstance {
+    /// Creates a running server for the given match, bound to a free local port, for tests.
+    pub async fn for_tests(game_instance: Arc<GameInstance>) -> Self {
+        ServerInstance {
+            socket: TcpListener::bind((HOST, 0))
+                .await
+                .expect("No free local port"),
+            listening: Arc::new(RwLock::new(true)),
+            game_instance,
+            exit_status: Arc::new(RwLock::new(None)),
+            connected_clients: Arc::new(RwLock::new(HashMap::new())),
+            tls_acceptor: None,
+            shutdown: watch::channel(false).0,
+        }
+    }
+}
+
 pub struct UninitializedServer {
     pub socket: TcpListener,
     pub listening: Arc<RwLock<bool>>,
+    pub tls_acceptor: Option<TlsAcceptor>,
 }
 
 impl UninitializedServer {
+    /// Binds the match listener on the given port.
+    ///
+    /// Loads the TLS certificate and key from the settings when configured, so every accepted
+    /// connection, including the initialization one, is encrypted.
+    ///
+    /// # Arguments
+    /// * `port` - The port to listen on.
+    ///
+    /// # Returns
+    /// * `Ok(UninitializedServer)` - The server waiting for its `InitServer` request.
+    /// * `Err(Error)` - If the port cannot be bound or the TLS configuration is invalid.
     pub async fn create_instance(port: u16) -> Result<Self, Error> {
+        let settings = SETTINGS.get().expect("Settings not initialized");
+        let tls_acceptor = tls::load_acceptor(settings)?;
+
         match TcpListener::bind((HOST, port)).await {
             Ok(listener) => {
-                logger!(INFO, "[SERVER] Listening on port `{port}`");
+                let transport = match tls_acceptor {
+                    Some(_) => "TLS",
+                    None => "TCP",
+                };
+                logger!(INFO, "[SERVER] Listening on port `{port}` ({transport})");
                 Ok(Self {
                     socket: listener,
-                    listening: Arc::new(RwLock::new(false)),
+                    listening: Arc::new(RwLock::new(true)),
+                    tls_acceptor,
                 })
             }
             Err(error) => Err(error),
@@ -115,9 +288,14 @@ impl UninitializedServer {
                     logger!(INFO, "[SERVER] Failed to accept client connection: {error}");
                     Err(ServerInstanceError::PlaceHolderError)
                 }
-                Ok((stream, _)) => {
-                    let me = self.clone();
-                    me.listen_to_connection(stream).await
+                Ok((stream, addr)) => {
+                    match transport::accept(stream, self.tls_acceptor.as_ref()).await {
+                        Ok(stream) => self.clone().listen_to_connection(stream).await,
+                        Err(error) => {
+                            logger!(WARN, "[SERVER] TLS handshake with `{addr}` failed: {error}");
+                            Err(ServerInstanceError::PlaceHolderError)
+                        }
+                    }
                 }
             }
         }
@@ -127,9 +305,10 @@ impl UninitializedServer {
 
     pub async fn listen_to_connection(
         self: Arc<Self>,
-        mut stream: TcpStream,
+        mut stream: BoxedTransport,
     ) -> Result<ServerInstance, ServerInstanceError> {
         let mut buffer = [0; 1024];
+        let mut decoder = PacketDecoder::new();
         while *self.listening.read().await {
             let read_bytes = match stream.read(&mut buffer).await {
                 Ok(0) => return Err(ServerInstanceError::PlaceHolderError),
@@ -139,38 +318,37 @@ impl UninitializedServer {
 
             let mut send_packet = async |packet: Packet| {
                 let _ = stream.write(&packet.wrap_packet()).await;
+                let _ = stream.flush().await;
             };
 
-            match Packet::parse(&buffer[..read_bytes]) {
-                Ok(packet) => {
-                    if packet.header.header_type == HeaderType::InitServer {
-                        return match serde_cbor::from_slice::<InitServerRequest>(&packet.payload) {
-                            Err(error) => {
-                                let packet =
-                                    Packet::new(HeaderType::ERROR, error.to_string().as_bytes());
-                                send_packet(packet).await;
-                                Err(ServerInstanceError::PlaceHolderError)
-                            }
-                            Ok(request) => {
-                                match ServerInstance::init_server(self.clone(), request).await {
-                                    Ok(server) => Ok(server),
-                                    Err(error) => {
-                                        let packet = Packet::new(
-                                            HeaderType::ERROR,
-                                            error.to_string().as_bytes(),
-                                        );
-                                        send_packet(packet).await;
-                                        Err(ServerInstanceError::PlaceHolderError)
-                                    }
+            decoder.extend(&buffer[..read_bytes]);
+            loop {
+                match decoder.next_packet() {
+                    Ok(None) => break,
+                    Ok(Some(packet)) => match Message::decode(&packet) {
+                        Ok(Message::InitServer(request)) => {
+                            return match ServerInstance::init_server(self.clone(), request).await {
+                                Ok(server) => Ok(server),
+                                Err(error) => {
+                                    let packet = Packet::error(HeaderType::ERROR, &error);
+                                    send_packet(packet).await;
+                                    Err(ServerInstanceError::PlaceHolderError)
                                 }
-                            }
-                        };
+                            };
+                        }
+                        Ok(_) => {}
+                        Err(error) => {
+                            let packet = Packet::error(HeaderType::ERROR, &error);
+                            send_packet(packet).await;
+                            return Err(ServerInstanceError::PlaceHolderError);
+                        }
+                    },
+                    Err(error) => {
+                        let packet = Packet::error(HeaderType::ERROR, &error);
+                        send_packet(packet).await;
+                        break;
                     }
                 }
-                Err(error) => {
-                    let packet = Packet::new(HeaderType::ERROR, error.to_string().as_bytes());
-                    send_packet(packet).await;
-                }
             }
         }
 
@@ -0,0 +1,114 @@
+use crate::models::settings::Settings;
+use std::io::{Error, ErrorKind};
+use std::sync::Arc;
+use tokio_rustls::rustls::crypto::ring;
+use tokio_rustls::rustls::pki_types::pem::PemObject;
+use tokio_rustls::rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use tokio_rustls::rustls::ServerConfig;
+use tokio_rustls::TlsAcceptor;
+
+/// Loads the TLS acceptor configured in the settings.
+///
+/// TLS is enabled when both `TLS_CERT_PATH` and `TLS_KEY_PATH` are set.
+///
+/// # Arguments
+/// - `settings`: The server settings.
+///
+/// # Returns
+/// - `Ok(Some(TlsAcceptor))`: If TLS is enabled and the certificate and key are valid.
+/// - `Ok(None)`: If TLS is disabled.
+/// - `Err(Error)`: If only one of the paths is set, or the files cannot be read or used.
+pub fn load_acceptor(settings: &Settings) -> Result<Option<TlsAcceptor>, Error> {
+    match (&settings.tls_cert_path, &settings.tls_key_path) {
+        (None, None) => Ok(None),
+        (Some(cert_path), Some(key_path)) => {
+            let cert = std::fs::read(cert_path)?;
+            let key = std::fs::read(key_path)?;
+            Ok(Some(acceptor_from_pem(&cert, &key)?))
+        }
+        _ => Err(Error::new(
+            ErrorKind::InvalidInput,
+            "TLS_CERT_PATH and TLS_KEY_PATH must be set together",
+        )),
+    }
+}
+
+/// Creates a TLS acceptor from a PEM certificate chain and private key.
+///
+/// # Arguments
+/// - `cert`: The PEM-encoded certificate chain, leaf certificate first.
+/// - `key`: The PEM-encoded private key of the leaf certificate.
+///
+/// # Returns
+/// - `Ok(TlsAcceptor)`: The acceptor used to wrap accepted TCP streams.
+/// - `Err(Error)`: If the certificate or key is invalid.
+pub fn acceptor_from_pem(cert: &[u8], key: &[u8]) -> Result<TlsAcceptor, Error> {
+    let invalid = |error: String| Error::new(ErrorKind::InvalidInput, error);
+
+    let certs = CertificateDer::pem_slice_iter(cert)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|error| invalid(format!("Invalid TLS certificate: {error}")))?;
+    let key = PrivateKeyDer::from_pem_slice(key)
+        .map_err(|error| invalid(format!("Invalid TLS private key: {error}")))?;
+
+    let config = ServerConfig::builder_with_provider(Arc::new(ring::default_provider()))
+        .with_safe_default_protocol_versions()
+        .map_err(|error| invalid(error.to_string()))?
+        .with_no_client_auth()
+        .with_single_cert(certs, key)
+        .map_err(|error| invalid(error.to_string()))?;
+
+    Ok(TlsAcceptor::from(Arc::new(config)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tcp::transport;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::{TcpListener, TcpStream};
+    use tokio_rustls::rustls::pki_types::ServerName;
+    use tokio_rustls::rustls::{ClientConfig, RootCertStore};
+    use tokio_rustls::TlsConnector;
+
+    #[tokio::test]
+    async fn test_tls_stream_roundtrip() {
+        let certified = rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+        let acceptor = acceptor_from_pem(
+            certified.cert.pem().as_bytes(),
+            certified.key_pair.serialize_pem().as_bytes(),
+        )
+        .unwrap();
+
+        let mut roots = RootCertStore::empty();
+        roots.add(certified.cert.der().clone()).unwrap();
+        let client_config = ClientConfig::builder_with_provider(Arc::new(ring::default_provider()))
+            .with_safe_default_protocol_versions()
+            .unwrap()
+            .with_root_certificates(roots)
+            .with_no_client_auth();
+        let connector = TlsConnector::from(Arc::new(client_config));
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let client = tokio::spawn(async move {
+            let tcp = TcpStream::connect(addr).await.unwrap();
+            let server_name = ServerName::try_from("localhost").unwrap();
+            let mut tls = connector.connect(server_name, tcp).await.unwrap();
+            tls.write_all(b"encrypted").await.unwrap();
+            tls.flush().await.unwrap();
+        });
+
+        let (tcp, _) = listener.accept().await.unwrap();
+        let mut stream = transport::accept(tcp, Some(&acceptor)).await.unwrap();
+        let mut buffer = [0; 9];
+        stream.read_exact(&mut buffer).await.unwrap();
+        assert_eq!(&buffer, b"encrypted");
+        client.await.unwrap();
+    }
+
+    #[test]
+    fn test_tls_invalid_certificate() {
+        assert!(acceptor_from_pem(b"not a certificate", b"not a key").is_err());
+    }
+}
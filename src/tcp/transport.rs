@@ -0,0 +1,126 @@
+use std::io::Error;
+use tokio::io::{AsyncRead, AsyncWrite, ReadHalf, WriteHalf};
+use tokio::net::TcpStream;
+use tokio_rustls::TlsAcceptor;
+
+/// A byte stream a client can be served over.
+///
+/// Implemented for every `AsyncRead + AsyncWrite` type, such as plain TCP, TLS, WebSockets
+/// (through `WebSocketTransport`) or in-memory duplex streams, so `Client` and
+/// `TemporaryClient` do not need to know which transport a client is using.
+pub trait Transport: AsyncRead + AsyncWrite + Send + Sync + Unpin {}
+
+impl<T: AsyncRead + AsyncWrite + Send + Sync + Unpin> Transport for T {}
+
+/// A transport whose concrete type is only known at runtime.
+pub type BoxedTransport = Box<dyn Transport>;
+/// The read half of a client's transport.
+pub type TransportReader = ReadHalf<BoxedTransport>;
+/// The write half of a client's transport.
+pub type TransportWriter = WriteHalf<BoxedTransport>;
+
+/// Wraps an accepted TCP stream, performing the TLS handshake when an acceptor is configured.
+///
+/// # Arguments
+/// - `stream`: The TCP stream returned by the listener.
+/// - `tls_acceptor`: The server's TLS acceptor, if TLS is enabled.
+///
+/// # Returns
+/// - `Ok(BoxedTransport)`: The transport, ready to be read from and written to.
+/// - `Err(Error)`: If the TLS handshake failed.
+pub async fn accept(
+    stream: TcpStream,
+    tls_acceptor: Option<&TlsAcceptor>,
+) -> Result<BoxedTransport, Error> {
+    match tls_acceptor {
+        Some(acceptor) => Ok(Box::new(acceptor.accept(stream).await?)),
+        None => Ok(Box::new(stream)),
+    }
+}
+
+/// Splits a transport into halves that can be read from and written to concurrently.
+pub fn split(transport: BoxedTransport) -> (TransportReader, TransportWriter) {
+    tokio::io::split(transport)
+}
+
+/// Creates an in-memory transport, used to drive clients in tests.
+///
+/// # Arguments
+/// - `max_buf_size`: The amount of bytes each direction buffers before writes wait for reads.
+///
+/// # Returns
+/// The transport to hand to the server, and the stream acting as the remote client.
+#[cfg(test)]
+pub fn memory(max_buf_size: usize) -> (BoxedTransport, tokio::io::DuplexStream) {
+    let (server, client) = tokio::io::duplex(max_buf_size);
+    (Box::new(server), client)
+}
+
+/// Reads packets from the remote side of an in-memory transport until one of the given type
+/// arrives, skipping any other packet. Panics if none arrives within a second.
+///
+/// Packets are read one at a time, so the ones following it are left in the stream.
+#[cfg(test)]
+pub async fn receive(
+    remote: &mut tokio::io::DuplexStream,
+    header_type: crate::tcp::header::HeaderType,
+) -> crate::tcp::packet::Packet {
+    use crate::tcp::header::{Header, HEADER_SIZE};
+    use crate::tcp::packet::Packet;
+    use tokio::io::AsyncReadExt;
+
+    let receive = async {
+        loop {
+            let mut bytes = vec![0; HEADER_SIZE];
+            remote.read_exact(&mut bytes).await.unwrap();
+            let header_size = Header::required_size(&bytes);
+            bytes.resize(header_size, 0);
+            remote.read_exact(&mut bytes[HEADER_SIZE..]).await.unwrap();
+
+            let payload_length = Header::from_bytes(&bytes).unwrap().payload_length as usize;
+            bytes.resize(header_size + payload_length, 0);
+            remote.read_exact(&mut bytes[header_size..]).await.unwrap();
+
+            let packet = Packet::parse(&bytes).unwrap();
+            if packet.header.header_type == header_type {
+                return packet;
+            }
+        }
+    };
+
+    tokio::time::timeout(std::time::Duration::from_secs(1), receive)
+        .await
+        .expect("No packet received")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tcp::decoder::PacketDecoder;
+    use crate::tcp::header::HeaderType;
+    use crate::tcp::packet::Packet;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    #[tokio::test]
+    async fn test_memory_transport_roundtrip() {
+        let (transport, mut remote) = memory(1024);
+        let (mut reader, mut writer) = split(transport);
+
+        let packet = Packet::new(HeaderType::PlayCard, b"in memory").wrap_packet();
+        remote.write_all(&packet).await.unwrap();
+
+        let mut decoder = PacketDecoder::new();
+        let mut buffer = [0; 1024];
+        let bytes = reader.read(&mut buffer).await.unwrap();
+        decoder.extend(&buffer[..bytes]);
+        let decoded = decoder.next_packet().unwrap().unwrap();
+        assert_eq!(&*decoded.payload, b"in memory");
+
+        // Writes on the server half reach the remote side untouched
+        let answer = Packet::new(HeaderType::Pong, b"").wrap_packet();
+        writer.write_all(&answer).await.unwrap();
+        let mut received = vec![0; answer.len()];
+        remote.read_exact(&mut received).await.unwrap();
+        assert_eq!(&*received, &*answer);
+    }
+}
@@ -0,0 +1,149 @@
+use crate::tcp::transport::BoxedTransport;
+use futures_util::{SinkExt, StreamExt};
+use std::io::Error;
+use std::pin::Pin;
+use std::task::{ready, Context, Poll};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio_tungstenite::tungstenite::{Bytes, Message};
+use tokio_tungstenite::WebSocketStream;
+
+/// Adapts a WebSocket connection to the byte stream expected by `Client`.
+///
+/// Every write is sent as a single binary frame, and the payloads of incoming binary frames are
+/// read back to back, so the usual `Packet` framing (header + CBOR) is carried unchanged and
+/// reassembled by the `PacketDecoder`. Text frames are ignored, and pings are answered by the
+/// WebSocket implementation.
+pub struct WebSocketTransport {
+    socket: WebSocketStream<BoxedTransport>,
+    pending: Bytes,
+}
+
+impl WebSocketTransport {
+    /// Performs the WebSocket handshake over an accepted transport.
+    ///
+    /// # Arguments
+    /// - `stream`: The accepted transport, encrypted when TLS is enabled.
+    ///
+    /// # Returns
+    /// - `Ok(WebSocketTransport)`: The upgraded connection.
+    /// - `Err(Error)`: If the client did not complete a valid WebSocket handshake.
+    pub async fn accept(stream: BoxedTransport) -> Result<Self, Error> {
+        let socket = tokio_tungstenite::accept_async(stream)
+            .await
+            .map_err(Error::other)?;
+        Ok(Self {
+            socket,
+            pending: Bytes::new(),
+        })
+    }
+}
+
+impl AsyncRead for WebSocketTransport {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<Result<(), Error>> {
+        let this = self.get_mut();
+        loop {
+            if !this.pending.is_empty() {
+                let length = this.pending.len().min(buf.remaining());
+                buf.put_slice(&this.pending.split_to(length));
+                return Poll::Ready(Ok(()));
+            }
+
+            match ready!(this.socket.poll_next_unpin(cx)) {
+                Some(Ok(Message::Binary(data))) => this.pending = data,
+                Some(Ok(Message::Close(_))) | None => return Poll::Ready(Ok(())),
+                Some(Ok(_)) => continue,
+                Some(Err(error)) => return Poll::Ready(Err(Error::other(error))),
+            }
+        }
+    }
+}
+
+impl AsyncWrite for WebSocketTransport {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<Result<usize, Error>> {
+        let this = self.get_mut();
+        ready!(this.socket.poll_ready_unpin(cx)).map_err(Error::other)?;
+        this.socket
+            .start_send_unpin(Message::binary(buf.to_vec()))
+            .map_err(Error::other)?;
+        Poll::Ready(Ok(buf.len()))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Error>> {
+        self.get_mut()
+            .socket
+            .poll_flush_unpin(cx)
+            .map_err(Error::other)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Error>> {
+        self.get_mut()
+            .socket
+            .poll_close_unpin(cx)
+            .map_err(Error::other)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tcp::decoder::PacketDecoder;
+    use crate::tcp::header::HeaderType;
+    use crate::tcp::packet::Packet;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::{TcpListener, TcpStream};
+
+    #[tokio::test]
+    async fn test_websocket_packet_roundtrip() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let client = tokio::spawn(async move {
+            let tcp = TcpStream::connect(addr).await.unwrap();
+            let (mut socket, _) = tokio_tungstenite::client_async("ws://localhost/", tcp)
+                .await
+                .unwrap();
+
+            // A packet split across two frames is reassembled by the decoder
+            let packet = Packet::new(HeaderType::PlayCard, b"websocket").wrap_packet();
+            socket
+                .send(Message::binary(packet[..4].to_vec()))
+                .await
+                .unwrap();
+            socket
+                .send(Message::binary(packet[4..].to_vec()))
+                .await
+                .unwrap();
+
+            match socket.next().await {
+                Some(Ok(Message::Binary(data))) => data,
+                _ => panic!("Expected a binary frame"),
+            }
+        });
+
+        let (tcp, _) = listener.accept().await.unwrap();
+        let mut transport = WebSocketTransport::accept(Box::new(tcp)).await.unwrap();
+        let mut decoder = PacketDecoder::new();
+        let mut buffer = [0; 1024];
+        let packet = loop {
+            let bytes = transport.read(&mut buffer).await.unwrap();
+            decoder.extend(&buffer[..bytes]);
+            if let Some(packet) = decoder.next_packet().unwrap() {
+                break packet;
+            }
+        };
+        assert_eq!(&*packet.payload, b"websocket");
+
+        // Each packet written by the server is a single binary frame
+        let answer = Packet::new(HeaderType::Pong, b"").wrap_packet();
+        transport.write_all(&answer).await.unwrap();
+        transport.flush().await.unwrap();
+        assert_eq!(&*client.await.unwrap(), &*answer);
+    }
+}
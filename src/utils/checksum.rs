@@ -1,4 +1,46 @@
-/// A simple checksum utility for validating data integrity using XOR.
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// The integrity algorithm used to checksum a packet's payload.
+///
+/// Each variant maps to the lower half of the packet header's end byte, so the receiver knows
+/// how to validate the payload without any prior negotiation.
+///
+/// # Variants
+/// - `Xor` - Legacy 8-bit XOR folded into 16 bits (`0x0A`).
+/// - `Crc16` - CRC-16/CCITT-FALSE (`0x0B`).
+/// - `Crc32` - CRC-32/ISO-HDLC (`0x0C`), whose lower 16 bits are carried after the end byte.
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum ChecksumAlgorithm {
+    #[default]
+    Xor = 0x0A,
+    Crc16 = 0x0B,
+    Crc32 = 0x0C,
+}
+
+impl TryFrom<u8> for ChecksumAlgorithm {
+    type Error = ();
+
+    /// Attempts to convert a header end byte into a `ChecksumAlgorithm`.
+    ///
+    /// # Returns
+    /// - `Ok(ChecksumAlgorithm)`: If the value matches a known algorithm.
+    /// - `Err(())`: If the value does not correspond to any supported algorithm.
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0x0A => Ok(ChecksumAlgorithm::Xor),
+            0x0B => Ok(ChecksumAlgorithm::Crc16),
+            0x0C => Ok(ChecksumAlgorithm::Crc32),
+            _ => Err(()),
+        }
+    }
+}
+
+/// A checksum utility for validating data integrity.
+///
+/// Supports the legacy XOR checksum as well as CRC-16 and CRC-32.
 pub struct Checksum;
 
 impl Checksum {
@@ -38,6 +80,74 @@ impl Checksum {
         // Compare the provided checksum with the computed checksum
         return *checksum == check as i16;
     }
+
+    /// Computes the checksum of the payload using the given algorithm.
+    ///
+    /// # Arguments
+    ///
+    /// * `algorithm` - The integrity algorithm to use.
+    /// * `payload` - A byte slice containing the data to compute the checksum for.
+    ///
+    /// # Returns
+    ///
+    /// The checksum widened to a `u32`. 16-bit algorithms leave the upper half zeroed.
+    pub fn compute(algorithm: &ChecksumAlgorithm, payload: &[u8]) -> u32 {
+        match algorithm {
+            ChecksumAlgorithm::Xor => Checksum::new(payload) as u32,
+            ChecksumAlgorithm::Crc16 => Checksum::crc16(payload) as u32,
+            ChecksumAlgorithm::Crc32 => Checksum::crc32(payload),
+        }
+    }
+
+    /// Verifies that the provided checksum matches the payload for the given algorithm.
+    ///
+    /// # Arguments
+    ///
+    /// * `algorithm` - The integrity algorithm the checksum was computed with.
+    /// * `checksum` - The expected checksum.
+    /// * `payload` - A byte slice containing the data to validate.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the provided checksum matches the computed checksum; `false` otherwise.
+    pub fn verify(algorithm: &ChecksumAlgorithm, checksum: u32, payload: &[u8]) -> bool {
+        match algorithm {
+            ChecksumAlgorithm::Xor => {
+                checksum <= u16::MAX as u32 && Checksum::check(&(checksum as i16), payload)
+            }
+            _ => Checksum::compute(algorithm, payload) == checksum,
+        }
+    }
+
+    /// Computes a CRC-16/CCITT-FALSE checksum (poly `0x1021`, init `0xFFFF`).
+    pub fn crc16(payload: &[u8]) -> u16 {
+        let mut crc: u16 = 0xFFFF;
+        for &byte in payload {
+            crc ^= (byte as u16) << 8;
+            for _ in 0..8 {
+                crc = match crc & 0x8000 {
+                    0 => crc << 1,
+                    _ => (crc << 1) ^ 0x1021,
+                };
+            }
+        }
+        crc
+    }
+
+    /// Computes a CRC-32/ISO-HDLC checksum (reflected poly `0xEDB88320`), as used by zlib.
+    pub fn crc32(payload: &[u8]) -> u32 {
+        let mut crc: u32 = 0xFFFFFFFF;
+        for &byte in payload {
+            crc ^= byte as u32;
+            for _ in 0..8 {
+                crc = match crc & 1 {
+                    0 => crc >> 1,
+                    _ => (crc >> 1) ^ 0xEDB88320,
+                };
+            }
+        }
+        !crc
+    }
 }
 
 #[cfg(test)]
@@ -84,4 +194,29 @@ mod tests {
         // Verify that the checksum validation fails for an invalid checksum
         assert!(!Checksum::check(&bad_checksum, payload));
     }
+
+    #[test]
+    fn test_crc16_check_value() {
+        // Standard check value for CRC-16/CCITT-FALSE
+        assert_eq!(Checksum::crc16(b"123456789"), 0x29B1);
+    }
+
+    #[test]
+    fn test_crc32_check_value() {
+        // Standard check value for CRC-32/ISO-HDLC
+        assert_eq!(Checksum::crc32(b"123456789"), 0xCBF43926);
+    }
+
+    #[test]
+    fn test_crc_detects_swapped_bytes() {
+        let payload: &[u8] = &[0x10, 0x20, 0x30];
+        let swapped: &[u8] = &[0x20, 0x10, 0x30];
+        // XOR cannot tell the payloads apart, the CRCs can
+        assert_eq!(Checksum::new(payload), Checksum::new(swapped));
+        for algorithm in [ChecksumAlgorithm::Crc16, ChecksumAlgorithm::Crc32] {
+            let checksum = Checksum::compute(&algorithm, payload);
+            assert!(Checksum::verify(&algorithm, checksum, payload));
+            assert!(!Checksum::verify(&algorithm, checksum, swapped));
+        }
+    }
 }
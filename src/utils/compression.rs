@@ -0,0 +1,67 @@
+use crate::utils::errors::ProtocolError;
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use flate2::Compression;
+use std::io::{Read, Write};
+
+/// Smallest payload worth compressing; smaller payloads are sent as is.
+pub const COMPRESSION_THRESHOLD: usize = 1024;
+
+/// Compresses a payload with raw DEFLATE.
+///
+/// # Arguments
+/// - `payload`: The uncompressed payload.
+///
+/// # Returns
+/// - `Some(Vec<u8>)`: The compressed payload.
+/// - `None`: If compressing did not make the payload any smaller.
+pub fn compress(payload: &[u8]) -> Option<Vec<u8>> {
+    let mut encoder = DeflateEncoder::new(Vec::new(), Compression::fast());
+    encoder.write_all(payload).ok()?;
+    let compressed = encoder.finish().ok()?;
+    (compressed.len() < payload.len()).then_some(compressed)
+}
+
+/// Decompresses a raw DEFLATE payload.
+///
+/// # Arguments
+/// - `payload`: The compressed payload.
+/// - `max_length`: The largest decompressed size accepted, guarding against compression bombs.
+///
+/// # Returns
+/// - `Ok(Vec<u8>)`: The decompressed payload.
+/// - `Err(ProtocolError)`: If the payload is not valid DEFLATE data or exceeds `max_length`.
+pub fn decompress(payload: &[u8], max_length: u32) -> Result<Vec<u8>, ProtocolError> {
+    let mut decompressed = Vec::new();
+    DeflateDecoder::new(payload)
+        .take(max_length as u64 + 1)
+        .read_to_end(&mut decompressed)
+        .map_err(|error| ProtocolError::InvalidPacketError(error.to_string()))?;
+
+    if decompressed.len() > max_length as usize {
+        return Err(ProtocolError::InvalidPacketError(format!(
+            "Decompressed payload exceeds limit: {max_length}"
+        )));
+    }
+
+    Ok(decompressed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compression_roundtrip() {
+        let payload = vec![0x2A; 4096];
+        let compressed = compress(&payload).unwrap();
+        assert!(compressed.len() < payload.len());
+        assert_eq!(decompress(&compressed, 4096).unwrap(), payload);
+    }
+
+    #[test]
+    fn test_decompression_limit() {
+        let compressed = compress(&[0x2A; 4096]).unwrap();
+        assert!(decompress(&compressed, 1024).is_err());
+    }
+}
@@ -18,6 +18,9 @@ pub enum PlayerConnectionError {
     #[error("Player token was not authorized")]
     UnauthorizedPlayerError,
 
+    #[error("Protocol version `{0}` is not supported")]
+    UnsupportedProtocolVersion(u16),
+
     #[error("Unexpected player error: {0}")]
     UnexpectedPlayerError(String),
 
@@ -44,12 +47,27 @@ pub enum ProtocolError {
 
     #[error("Invalid packet: {0}")]
     InvalidPacketError(String),
+
+    #[error("Invalid packet payload: {0}")]
+    InvalidPacketPayload(String),
+
+    #[error("Invalid checksum value")]
+    InvalidChecksum,
+
+    #[error("Payload is compressed but compression was not negotiated")]
+    CompressionNotNegotiated,
 }
 
 #[derive(Debug, thiserror::Error)]
 pub enum NetworkError {
     #[error("Could not send package: {0}")]
     PackageWriteError(String),
+
+    #[error("Payload of {0} bytes exceeds the negotiated maximum")]
+    PayloadTooLarge(u32),
+
+    #[error("Outbound queue is full")]
+    OutboundQueueFull,
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -77,6 +95,33 @@ pub enum GameLogicError {
 
     #[error("Not player's turn")]
     NotPlayerTurn,
+
+    #[error("Attacker is not a creature on the player's board")]
+    AttackerNotOnBoard,
+
+    #[error("Attacker is exhausted")]
+    AttackerExhausted,
+
+    #[error("Attack target is not an enemy creature or hero")]
+    InvalidAttackTarget,
+
+    #[error("Action target `{0}` is not a hero or a creature on the board")]
+    InvalidActionTarget(String),
+
+    #[error("Summon position `{0}` is not a free board slot")]
+    InvalidSummonPosition(String),
+
+    #[error("Not enough mana: {0} required, {1} available")]
+    NotEnoughMana(i32, i32),
+
+    #[error("Mulligan is not available to the player")]
+    MulliganUnavailable,
+
+    #[error("Card `{0}` cannot be returned: it is not in the player's hand")]
+    InvalidMulliganCard(String),
+
+    #[error("The match is over")]
+    MatchOver,
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -100,7 +145,10 @@ pub enum CardRequestError {
 #[derive(Debug, thiserror::Error)]
 pub enum GameInstanceError {
     #[error("Placeholder error, make a specific one")]
-    PlaceHolderError
+    PlaceHolderError,
+
+    #[error("A match requires two players, got {0}")]
+    InvalidPlayerCount(usize),
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -1,3 +1,4 @@
 pub mod checksum;
+pub mod compression;
 pub mod errors;
 pub mod logger;